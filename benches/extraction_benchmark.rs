@@ -1,13 +1,32 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tailwind_extractor::{TailwindBuilder, TailwindClassProcessor};
 
-fn placeholder_benchmark(c: &mut Criterion) {
-    c.bench_function("placeholder", |b| {
-        b.iter(|| {
-            // Placeholder benchmark
-            black_box(42)
-        })
+struct BenchProcessor {
+    builder: TailwindBuilder,
+}
+
+impl TailwindClassProcessor for BenchProcessor {
+    fn tailwind_builder(&mut self) -> &mut TailwindBuilder {
+        &mut self.builder
+    }
+}
+
+fn process_benchmark(c: &mut Criterion) {
+    let mut processor = BenchProcessor {
+        builder: TailwindBuilder::default(),
+    };
+
+    // Projects that mostly use their own CSS hit the custom-class fast path
+    let custom = "btn btn-primary card card-header is-open active nav-link";
+    c.bench_function("process_all_custom_classes", |b| {
+        b.iter(|| processor.process_with_fallback(black_box(custom), false))
+    });
+
+    let tailwind = "flex items-center px-4 py-2 bg-blue-500 hover:bg-blue-600 text-white";
+    c.bench_function("process_tailwind_classes", |b| {
+        b.iter(|| processor.process_with_fallback(black_box(tailwind), false))
     });
 }
 
-criterion_group!(benches, placeholder_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, process_benchmark);
+criterion_main!(benches);
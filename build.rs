@@ -0,0 +1,39 @@
+//! Build script recording the resolved tailwind-rs version for the metadata manifest
+
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    // Cargo.lock is only present when building this package as the root crate
+    let Ok(lock) = fs::read_to_string("Cargo.lock") else {
+        return;
+    };
+
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line != "name = \"tailwind-rs\"" {
+            continue;
+        }
+
+        let field = |line: Option<&str>, key: &str| {
+            line.and_then(|l| l.strip_prefix(key))
+                .and_then(|l| l.strip_prefix(" = \""))
+                .and_then(|l| l.strip_suffix('"'))
+                .map(str::to_string)
+        };
+        let Some(version) = field(lines.next(), "version") else {
+            return;
+        };
+        // Git dependencies carry the resolved commit after `#`
+        let revision = field(lines.next(), "source")
+            .and_then(|source| source.rsplit_once('#').map(|(_, rev)| rev.chars().take(7).collect::<String>()));
+
+        let version = match revision {
+            Some(revision) => format!("{}+{}", version, revision),
+            None => version,
+        };
+        println!("cargo:rustc-env=TAILWIND_RS_VERSION={}", version);
+        return;
+    }
+}
@@ -7,21 +7,24 @@
 //! - Returns transformed code and class metadata
 
 use anyhow::{Context, Result};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashMap;
 use swc_core::{
     common::{
-        comments::SingleThreadedComments, sync::Lrc, FileName, Globals, SourceMap,
-        GLOBALS,
+        comments::{Comment, Comments, SingleThreadedComments}, sync::Lrc, FileName, Globals, SourceMap, Span,
+        DUMMY_SP, GLOBALS,
     },
     ecma::{
         ast::*,
         codegen::{text_writer::JsWriter, Config as CodegenConfig, Emitter},
-        parser::{lexer::Lexer, Parser, StringInput, Syntax, TsSyntax},
+        parser::{lexer::Lexer, EsSyntax, Parser, StringInput, Syntax, TsSyntax},
         visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
     },
 };
 
-use crate::TailwindClassProcessor;
+use crate::class_utils::{is_class_like, is_obviously_custom};
+use crate::css::{apply_directive_classes, selector_class_names, single_rule_declarations, sort_class_list};
+use crate::processor::{TailwindClassProcessor, WhitespaceContext};
 use tailwind_rs::TailwindBuilder;
 
 /// Context tracking for AST traversal to avoid processing strings in wrong contexts
@@ -37,10 +40,37 @@ enum AstContext {
     FunctionCall(String),
     /// Inside an import statement
     ImportStatement,
+    /// Inside a switch discriminant or case test, where strings are compared, not applied
+    SwitchTest,
+}
+
+/// Whether a called function creates JSX elements: the automatic runtime (`jsx`,
+/// `_jsx`, `JsxRuntime.jsxs`) or a `createElement` helper, including ReScript's
+/// `React.createElementVariadic`
+fn is_jsx_factory(func_name: &str) -> bool {
+    let method = func_name.rsplit('.').next().unwrap_or(func_name);
+    func_name.contains("JsxRuntime")
+        || matches!(method, "jsx" | "jsxs" | "_jsx" | "createElement" | "createElementVariadic")
+}
+
+/// Whether a statement is a string literal expression, which at the start of a module
+/// or function body is a directive such as `"use client"` rather than a class list
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(_))))
+}
+
+/// The function an indirect call like `(0, r.jsx)(...)` invokes, as bundlers emit
+/// to call imports without a `this`
+fn indirect_callee(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(paren) => indirect_callee(&paren.expr),
+        Expr::Seq(seq) => seq.exprs.last().map_or(expr, |last| indirect_callee(last)),
+        _ => expr,
+    }
 }
 
 /// Parse Tailwind classes from a string, correctly handling arbitrary values with brackets
-fn parse_tailwind_classes(input: &str) -> Vec<String> {
+pub(crate) fn parse_tailwind_classes(input: &str) -> Vec<String> {
     let mut classes = Vec::new();
     let mut current_class = String::new();
     let mut bracket_depth = 0;
@@ -53,7 +83,10 @@ fn parse_tailwind_classes(input: &str) -> Vec<String> {
                 current_class.push(ch);
             }
             ']' => {
-                bracket_depth -= 1;
+                // A stray `]` must not leave us "inside" brackets for the rest of the string
+                if bracket_depth > 0 {
+                    bracket_depth -= 1;
+                }
                 current_class.push(ch);
                 // If we're back at depth 0 and have content, this might be end of a class
                 if bracket_depth == 0 && !current_class.is_empty() {
@@ -90,6 +123,69 @@ fn parse_tailwind_classes(input: &str) -> Vec<String> {
     classes
 }
 
+/// Best-effort scan of a css`...` block for the utility classes it references.
+///
+/// `@apply` lists are always collected; with `include_selectors`, class selectors
+/// such as `.md\:flex` are collected too when they look like utilities.
+fn css_template_classes(css: &str, include_selectors: bool) -> Vec<String> {
+    let mut classes = apply_directive_classes(css);
+
+    if include_selectors {
+        // Skip numbers like `0.5rem` and custom class names
+        classes.extend(selector_class_names(css).into_iter().filter(|class| {
+            class.starts_with(|c: char| c.is_ascii_alphabetic()) && !is_obviously_custom(class)
+        }));
+    }
+
+    classes
+}
+
+/// Elements of a JSON array of strings such as `["flex","p-4"]`, as produced by
+/// `JSON.stringify(classes)`
+fn json_class_array(value: &str) -> Option<Vec<String>> {
+    let trimmed = value.trim();
+    if !(trimmed.starts_with('[') && trimmed.ends_with(']')) {
+        return None;
+    }
+    serde_json::from_str(trimmed).ok()
+}
+
+/// Build a React style object property from a CSS declaration.
+///
+/// Property names are camel-cased (`background-color` -> `backgroundColor`,
+/// `-webkit-box` -> `WebkitBox`); custom properties keep their name as a string key.
+fn style_prop(name: &str, value: &str) -> PropOrSpread {
+    let key = if name.starts_with("--") {
+        PropName::Str(Str {
+            span: DUMMY_SP,
+            value: name.into(),
+            raw: None,
+        })
+    } else {
+        let mut camel = String::with_capacity(name.len());
+        for (i, part) in name.split('-').enumerate() {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) if i > 0 => {
+                    camel.push(first.to_ascii_uppercase());
+                    camel.push_str(chars.as_str());
+                }
+                _ => camel.push_str(part),
+            }
+        }
+        PropName::Ident(IdentName::new(camel.into(), DUMMY_SP))
+    };
+
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key,
+        value: Box::new(Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: value.into(),
+            raw: None,
+        }))),
+    })))
+}
+
 /// Metadata collected during AST transformation
 #[derive(Debug, Clone)]
 pub struct TransformMetadata {
@@ -97,8 +193,95 @@ pub struct TransformMetadata {
     pub classes: Vec<String>,
     /// Count of classes before deduplication
     pub original_count: usize,
+    /// Problems worth reporting that didn't stop the transform, e.g. dynamic class keys
+    pub warnings: Vec<String>,
+    /// `(line, column)` where each class was first seen; only the JavaScript front
+    /// end records positions
+    pub locations: IndexMap<String, (usize, usize)>,
+}
+
+/// Default for [`TransformConfig::max_nesting_depth`]
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 512;
+
+/// Deepest nesting of `()`, `[]` and `{}` in `source`.
+///
+/// Brackets inside string literals, template literal text and comments are skipped;
+/// `${...}` interpolations count. Quotes end at a line break, so an apostrophe in
+/// JSX text or a regex literal can't hide the rest of the file.
+fn nesting_depth(source: &str) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    // Depths of the open `${` interpolations; closing one resumes its template
+    let mut interpolations: Vec<usize> = Vec::new();
+    let mut in_template = false;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_template {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '`' => in_template = false,
+                '$' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    depth += 1;
+                    max_depth = max_depth.max(depth);
+                    interpolations.push(depth);
+                    in_template = false;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' | '\'' => {
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\n' => break,
+                        _ if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            '`' => in_template = true,
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&next| next == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            ')' | ']' | '}' => {
+                if c == '}' && interpolations.last() == Some(&depth) {
+                    interpolations.pop();
+                    in_template = true;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+    max_depth
 }
 
+/// Attribute and prop names whose values are always treated as class lists
+pub const CLASS_ATTRIBUTES: &[&str] = &["className", "class"];
+
 /// Configuration for AST transformation
 #[derive(Debug, Clone)]
 pub struct TransformConfig {
@@ -106,6 +289,55 @@ pub struct TransformConfig {
     pub obfuscate: bool,
     /// Whether to preserve source maps (if applicable)
     pub source_maps: bool,
+    /// Classes that are never obfuscated, e.g. names toggled by runtime JS
+    pub preserve_classes: Vec<String>,
+    /// When obfuscating, keep each original class and append its obfuscated name
+    /// (`bg-blue-500 x1`) so either stylesheet applies
+    pub obfuscate_additive: bool,
+    /// Extra attribute/prop names treated like `className`, e.g. `containerClassName`
+    pub class_attributes: Vec<String>,
+    /// Also record utility classes used as selectors inside css`...` blocks
+    pub extract_css_template_selectors: bool,
+    /// Move static JSX classes into inline `style` objects where possible
+    pub inline_css: bool,
+    /// Functions whose string arguments are always class lists, e.g. `myClassHelper`
+    pub class_functions: Vec<String>,
+    /// Process every string literal regardless of context, recording only the
+    /// tokens that generate Tailwind CSS (for bundles where context is lost)
+    pub aggressive: bool,
+    /// Prefix classes that fail to trace with `UNTRACED-` so they show up in output
+    pub mark_untraced: bool,
+    /// Remove static JSX class attributes whose classes all generate no CSS
+    pub strip_dead_classnames: bool,
+    /// Treat strings holding a JSON array of strings, e.g. `["flex","p-4"]`, as class lists
+    pub parse_json_classes: bool,
+    /// Trim edge whitespace from standalone JSX class attribute literals and the
+    /// class props of compiled JSX factory calls
+    pub trim_class_whitespace: bool,
+    /// Rewrite JSX class attribute literals in Tailwind's recommended class order
+    pub sort_class_order: bool,
+    /// Skip literal values of JSX attributes that aren't class attributes
+    /// (`part="label"`, `title="..."`) instead of processing them as class lists
+    pub class_attributes_only: bool,
+    /// Warn about empty class attributes (`className=""`), which are often bugs
+    pub include_empty: bool,
+    /// Sources nesting brackets deeper than this are skipped with a warning instead
+    /// of risking a stack overflow in the recursive parser and visitors
+    pub max_nesting_depth: usize,
+    /// Input is a minified bundle: parse it as plain JavaScript without collecting
+    /// comments, recognise compiled JSX calls like `(0, r.jsx)(...)`, and keep the
+    /// output minified
+    pub minified_input: bool,
+    /// Record classes listed in comments starting with this prefix, e.g.
+    /// `/* tailwind-safelist: bg-red-500 text-white */` (ignored with `minified_input`)
+    pub safelist_comment_prefix: Option<String>,
+}
+
+impl TransformConfig {
+    /// Whether values of the named attribute or prop are class lists
+    pub fn is_class_attribute(&self, name: &str) -> bool {
+        CLASS_ATTRIBUTES.contains(&name) || self.class_attributes.iter().any(|attr| attr == name)
+    }
 }
 
 impl Default for TransformConfig {
@@ -113,6 +345,23 @@ impl Default for TransformConfig {
         Self {
             obfuscate: false,
             source_maps: false,
+            preserve_classes: Vec::new(),
+            obfuscate_additive: false,
+            class_attributes: Vec::new(),
+            extract_css_template_selectors: false,
+            inline_css: false,
+            class_functions: Vec::new(),
+            aggressive: false,
+            mark_untraced: false,
+            strip_dead_classnames: false,
+            parse_json_classes: false,
+            trim_class_whitespace: false,
+            sort_class_order: false,
+            class_attributes_only: false,
+            include_empty: false,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            minified_input: false,
+            safelist_comment_prefix: None,
         }
     }
 }
@@ -129,10 +378,20 @@ struct TailwindTransformer {
     total_count: usize,
     /// Context stack for tracking where we are in the AST
     context_stack: Vec<AstContext>,
+    /// Inline declarations per class (`None` if the class can't be inlined)
+    inline_cache: HashMap<String, Option<Vec<(String, String)>>>,
+    /// Source map for turning spans into line/column locations
+    source_map: Lrc<SourceMap>,
+    /// Warnings collected while visiting
+    warnings: Vec<String>,
+    /// Whether a class generates CSS on its own, cached for aggressive mode
+    generates_css_cache: HashMap<String, bool>,
+    /// First `(line, column)` of each collected class
+    locations: IndexMap<String, (usize, usize)>,
 }
 
 impl TailwindTransformer {
-    fn new(config: TransformConfig) -> Result<Self> {
+    fn new(config: TransformConfig, source_map: Lrc<SourceMap>) -> Result<Self> {
         let tailwind = TailwindBuilder::default();
         Ok(Self {
             tailwind,
@@ -140,6 +399,11 @@ impl TailwindTransformer {
             classes: IndexSet::new(),
             total_count: 0,
             context_stack: vec![AstContext::TopLevel],
+            inline_cache: HashMap::new(),
+            source_map,
+            warnings: Vec::new(),
+            generates_css_cache: HashMap::new(),
+            locations: IndexMap::new(),
         })
     }
 
@@ -156,28 +420,293 @@ impl TailwindTransformer {
     }
 
     /// Process a string literal and transform its classes
-    fn process_string(&mut self, value: &str) -> String {
+    fn process_string(&mut self, value: &str, span: Span) -> String {
+        if self.config.parse_json_classes {
+            if let Some(items) = json_class_array(value) {
+                let processed: Vec<String> =
+                    items.iter().map(|item| self.process_string(item, span)).collect();
+                return serde_json::to_string(&processed).unwrap_or_else(|_| value.to_string());
+            }
+        }
+
         // Always use trace() to process the string
-        let processed = match self.tailwind.trace(value, self.config.obfuscate) {
-            Ok(result) => result.into_owned(),
-            Err(_) => value.to_string(), // Fallback to original on error
+        let per_class = self.config.obfuscate_additive || self.contains_preserved_class(value);
+        let processed = if self.config.obfuscate && per_class {
+            self.trace_preserving(value)
+        } else {
+            self.trace(value, self.config.obfuscate)
         };
 
         // Extract individual classes for metadata
-        self.extract_classes(value);
+        self.extract_classes(value, span);
 
         processed
     }
 
+    /// Trace a string, falling back to the original (or marking untraced classes) on error
+    fn trace(&mut self, value: &str, obfuscate: bool) -> String {
+        if self.config.mark_untraced {
+            self.process_marking_untraced(value, obfuscate)
+        } else {
+            self.process_with_fallback(value, obfuscate)
+        }
+    }
+
+    /// Check whether any class in the string is excluded from obfuscation
+    fn contains_preserved_class(&self, value: &str) -> bool {
+        !self.config.preserve_classes.is_empty()
+            && value
+                .split_whitespace()
+                .any(|class| self.config.preserve_classes.iter().any(|p| p == class))
+    }
+
+    /// Trace a string class by class so preserved classes skip obfuscation and, in
+    /// additive mode, obfuscated names follow their originals.
+    ///
+    /// Whitespace runs are copied verbatim to keep concatenation boundaries intact.
+    fn trace_preserving(&mut self, value: &str) -> String {
+        let mut output = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while !rest.is_empty() {
+            let in_whitespace = rest.starts_with(char::is_whitespace);
+            let split = rest
+                .find(|c: char| c.is_whitespace() != in_whitespace)
+                .unwrap_or(rest.len());
+            let (segment, tail) = rest.split_at(split);
+
+            if in_whitespace {
+                output.push_str(segment);
+            } else {
+                let preserved = self.config.preserve_classes.iter().any(|p| p == segment);
+                let traced = self.trace(segment, !preserved);
+                if self.config.obfuscate_additive && traced != segment {
+                    output.push_str(segment);
+                    output.push(' ');
+                }
+                output.push_str(&traced);
+            }
+            rest = tail;
+        }
+
+        output
+    }
+
     /// Extract individual classes from a string for metadata collection
-    fn extract_classes(&mut self, value: &str) {
+    fn extract_classes(&mut self, value: &str, span: Span) {
         // Use a proper parser that handles arbitrary values with brackets
         let classes = parse_tailwind_classes(value);
         for class in classes {
+            // Aggressive mode sees arbitrary text, so only real utilities are recorded
+            if self.config.aggressive && !self.generates_css(&class) {
+                continue;
+            }
             if !class.is_empty() {
-                self.classes.insert(class);
-                self.total_count += 1;
+                self.record_class(class, span);
+            }
+        }
+    }
+
+    /// Record the utilities a CSS template references, leaving its text untouched
+    fn scan_css_template(&mut self, tpl: &mut Tpl, span: Span) {
+        let css = tpl
+            .quasis
+            .iter()
+            .filter_map(|quasi| quasi.cooked.as_ref().map(|cooked| cooked.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        for class in css_template_classes(&css, self.config.extract_css_template_selectors) {
+            self.record_class(class, span);
+        }
+
+        // Interpolations may still contain class strings
+        for expr in &mut tpl.exprs {
+            expr.visit_mut_with(self);
+        }
+    }
+
+    /// Record the classes listed after `prefix` in comments, in source order
+    fn record_comment_safelist(&mut self, comments: &SingleThreadedComments, prefix: &str) {
+        let (leading, trailing) = comments.borrow_all();
+        let mut all: Vec<&Comment> = leading.values().chain(trailing.values()).flatten().collect();
+        all.sort_by_key(|comment| comment.span.lo);
+
+        for comment in all {
+            // Lines of doc-style block comments start with `*`
+            for line in comment.text.lines() {
+                let line = line.trim().trim_start_matches('*').trim_start();
+                if let Some(list) = line.strip_prefix(prefix) {
+                    for class in parse_tailwind_classes(list) {
+                        self.record_class(class, comment.span);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record a collected class and, the first time it's seen, where it appeared
+    fn record_class(&mut self, class: String, span: Span) {
+        if !self.locations.contains_key(&class) {
+            let loc = self.source_map.lookup_char_pos(span.lo);
+            self.locations.insert(class.clone(), (loc.line, loc.col_display + 1));
+        }
+        self.classes.insert(class);
+        self.total_count += 1;
+    }
+
+    /// Inline declarations for a single class, or `None` if it can't be inlined
+    fn inline_declarations(&mut self, class: &str) -> Option<Vec<(String, String)>> {
+        if let Some(cached) = self.inline_cache.get(class) {
+            return cached.clone();
+        }
+
+        let mut builder = TailwindBuilder::default();
+        builder.preflight.disable = true;
+        let declarations = builder
+            .trace(class, false)
+            .ok()
+            .and_then(|_| builder.bundle().ok())
+            .and_then(|css| single_rule_declarations(&css));
+
+        self.inline_cache.insert(class.to_string(), declarations.clone());
+        declarations
+    }
+
+    /// Move the inlinable classes of a static class attribute into a `style` object.
+    ///
+    /// Classes with variants or unknown to Tailwind stay in the class attribute.
+    /// Elements that already have a `style` attribute are left alone.
+    fn inline_class_attribute(&mut self, node: &mut JSXOpeningElement) {
+        let attr_name = |attr: &JSXAttrOrSpread| match attr {
+            JSXAttrOrSpread::JSXAttr(JSXAttr { name: JSXAttrName::Ident(ident), .. }) => {
+                Some(ident.sym.to_string())
+            }
+            _ => None,
+        };
+        if node.attrs.iter().any(|attr| attr_name(attr).as_deref() == Some("style")) {
+            return;
+        }
+
+        let Some(index) = node.attrs.iter().position(|attr| {
+            matches!(attr, JSXAttrOrSpread::JSXAttr(JSXAttr { value: Some(JSXAttrValue::Lit(Lit::Str(_))), .. }))
+                && attr_name(attr).is_some_and(|name| self.config.is_class_attribute(&name))
+        }) else {
+            return;
+        };
+        let JSXAttrOrSpread::JSXAttr(JSXAttr { value: Some(JSXAttrValue::Lit(Lit::Str(class_str))), .. }) =
+            &mut node.attrs[index]
+        else {
+            return;
+        };
+
+        let class_string = class_str.value.to_string();
+        let mut remaining = Vec::new();
+        let mut props = Vec::new();
+        for class in class_string.split_whitespace() {
+            match self.inline_declarations(class) {
+                Some(declarations) => props.extend(
+                    declarations
+                        .into_iter()
+                        .map(|(name, value)| style_prop(&name, &value)),
+                ),
+                None => remaining.push(class),
+            }
+        }
+        if props.is_empty() {
+            return;
+        }
+
+        if remaining.is_empty() {
+            node.attrs.remove(index);
+        } else {
+            class_str.value = remaining.join(" ").into();
+            class_str.raw = None;
+        }
+
+        node.attrs.push(JSXAttrOrSpread::JSXAttr(JSXAttr {
+            span: DUMMY_SP,
+            name: JSXAttrName::Ident(IdentName::new("style".into(), DUMMY_SP)),
+            value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                span: DUMMY_SP,
+                expr: JSXExpr::Expr(Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                }))),
+            })),
+        }));
+    }
+
+    /// Remove static class attributes that only reference classes without CSS,
+    /// e.g. `className="definitely-not-a-class"`.
+    ///
+    /// Attributes containing a preserved class are kept, since runtime JS may rely on them.
+    fn strip_dead_class_attributes(&mut self, node: &mut JSXOpeningElement) {
+        let mut attrs = std::mem::take(&mut node.attrs);
+        attrs.retain(|attr| {
+            let JSXAttrOrSpread::JSXAttr(JSXAttr {
+                name: JSXAttrName::Ident(ident),
+                value: Some(JSXAttrValue::Lit(Lit::Str(class_str))),
+                ..
+            }) = attr
+            else {
+                return true;
+            };
+            if !self.config.is_class_attribute(&ident.sym) {
+                return true;
             }
+
+            let classes: Vec<&str> = class_str.value.split_whitespace().collect();
+            classes.is_empty()
+                || classes.iter().any(|class| {
+                    self.config.preserve_classes.iter().any(|p| p == class) || self.generates_css(class)
+                })
+        });
+        node.attrs = attrs;
+    }
+
+    /// Whether a single class generates any CSS
+    fn generates_css(&mut self, class: &str) -> bool {
+        if let Some(&generates) = self.generates_css_cache.get(class) {
+            return generates;
+        }
+
+        let generates = !is_obviously_custom(class) && {
+            let mut builder = TailwindBuilder::default();
+            builder.preflight.disable = true;
+            builder.trace(class, false).is_ok()
+                && builder.bundle().is_ok_and(|css| !css.trim().is_empty())
+        };
+
+        self.generates_css_cache.insert(class.to_string(), generates);
+        generates
+    }
+
+    /// Record a warning, prefixed with the `line:column` of `span`
+    fn warn(&mut self, span: Span, message: &str) {
+        let loc = self.source_map.lookup_char_pos(span.lo);
+        self.warnings
+            .push(format!("{}:{}: {}", loc.line, loc.col_display + 1, message));
+    }
+
+    /// Warn about an empty literal class attribute value when `include_empty` is set
+    fn check_empty_class_value(&mut self, name: &str, value: &Str) {
+        if self.config.include_empty && value.value.trim().is_empty() {
+            self.warn(value.span, &format!("empty `{}` attribute", name));
+        }
+    }
+
+    /// Trim edge whitespace from a literal class attribute value when configured.
+    ///
+    /// A literal value is the whole class list, unlike concatenated fragments, so
+    /// its edge whitespace is insignificant.
+    fn trim_class_literal(&self, class_str: &mut Str) {
+        if !self.config.trim_class_whitespace {
+            return;
+        }
+        let trimmed = WhitespaceContext::Standalone.normalize(&class_str.value);
+        if trimmed.len() != class_str.value.len() {
+            class_str.value = trimmed.into();
+            class_str.raw = None;
         }
     }
 
@@ -188,16 +717,33 @@ impl TailwindTransformer {
             return false;
         }
 
+        // Case labels are compared against values, they are not class lists
+        if matches!(self.context_stack.last(), Some(AstContext::SwitchTest)) {
+            return false;
+        }
+
+        if self.config.aggressive {
+            return true;
+        }
+
+        // Arguments of configured class functions are class lists wherever they appear
+        let in_class_function = self.context_stack.iter().any(|ctx| {
+            matches!(ctx, AstContext::FunctionCall(name) if self.config.class_functions.contains(name))
+        });
+        if in_class_function {
+            return true;
+        }
+
         // Check if we're in a JSX context
         let in_jsx = self.context_stack.iter().any(|ctx| {
-            matches!(ctx, AstContext::FunctionCall(name) if name == "_jsx" || name == "jsx" || name == "jsxs" || name == "createElement" || name.contains("JsxRuntime"))
+            matches!(ctx, AstContext::FunctionCall(name) if is_jsx_factory(name))
         });
 
         if in_jsx {
             // In JSX context, only process if we're in className/class props
             for ctx in self.context_stack.iter().rev() {
                 if let AstContext::JsxProps(Some(prop_name)) = ctx {
-                    return prop_name == "className" || prop_name == "class";
+                    return self.config.is_class_attribute(prop_name);
                 }
             }
             // If we're in JSX but not in a specific prop context,
@@ -225,13 +771,37 @@ impl VisitMut for TailwindTransformer {
 
     /// Visit string literals and transform them
     fn visit_mut_str(&mut self, node: &mut Str) {
+        // Aggressive mode sees every string, so skip the ones that can't be classes
+        if self.config.aggressive && !is_class_like(&node.value, false) {
+            return;
+        }
         if self.should_process_string() {
-            let processed = self.process_string(&node.value);
+            let processed = self.process_string(&node.value, node.span);
             node.value = processed.into();
             node.raw = None; // Clear raw to use processed value
         }
     }
 
+    /// Visit module bodies, skipping the directive prologue (`"use client"`, `"use strict"`)
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        let prologue = items
+            .iter()
+            .take_while(|item| matches!(item, ModuleItem::Stmt(stmt) if is_directive(stmt)))
+            .count();
+        for item in &mut items[prologue..] {
+            item.visit_mut_with(self);
+        }
+    }
+
+    /// Visit statement lists, skipping a leading directive prologue such as the
+    /// `"use server"` of a server action
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let prologue = stmts.iter().take_while(|stmt| is_directive(stmt)).count();
+        for stmt in &mut stmts[prologue..] {
+            stmt.visit_mut_with(self);
+        }
+    }
+
     /// Visit if statements to handle JSX assignments
     fn visit_mut_if_stmt(&mut self, node: &mut IfStmt) {
         // Visit the test condition
@@ -246,28 +816,107 @@ impl VisitMut for TailwindTransformer {
         }
     }
 
-    /// Visit JSX attributes (className, class)
+    /// Visit switch statements: case bodies are processed, case labels are not
+    fn visit_mut_switch_stmt(&mut self, node: &mut SwitchStmt) {
+        self.push_context(AstContext::SwitchTest);
+        node.discriminant.visit_mut_with(self);
+        self.pop_context();
+
+        for case in &mut node.cases {
+            if let Some(test) = &mut case.test {
+                self.push_context(AstContext::SwitchTest);
+                test.visit_mut_with(self);
+                self.pop_context();
+            }
+            for stmt in &mut case.cons {
+                stmt.visit_mut_with(self);
+            }
+        }
+    }
+
+    /// Visit JSX opening elements, inlining class styles in inline CSS mode
+    fn visit_mut_jsx_opening_element(&mut self, node: &mut JSXOpeningElement) {
+        if self.config.strip_dead_classnames {
+            self.strip_dead_class_attributes(node);
+        }
+        if self.config.inline_css {
+            self.inline_class_attribute(node);
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    /// Visit JSX attributes (className, class and configured class attributes)
     fn visit_mut_jsx_attr(&mut self, node: &mut JSXAttr) {
-        // Check if this is a className or class attribute
+        // Check if this is a class attribute
         if let JSXAttrName::Ident(ident) = &node.name {
-            if matches!(ident.sym.as_ref(), "className" | "class") {
+            if self.config.is_class_attribute(&ident.sym) {
                 // Visit the value specifically for class attributes
                 if let Some(value) = &mut node.value {
+                    if let JSXAttrValue::Lit(Lit::Str(class_str)) = value {
+                        self.check_empty_class_value(&ident.sym, class_str);
+                        if self.config.sort_class_order {
+                            let sorted = sort_class_list(&class_str.value);
+                            if sorted != *class_str.value {
+                                class_str.value = sorted.into();
+                                class_str.raw = None;
+                            }
+                        }
+                    }
                     value.visit_mut_children_with(self);
+                    if let JSXAttrValue::Lit(Lit::Str(class_str)) = value {
+                        self.trim_class_literal(class_str);
+                    }
                     return;
                 }
             }
         }
+        // Plain attribute text such as `part="label"` is only noise when restricted
+        if self.config.class_attributes_only && matches!(node.value, Some(JSXAttrValue::Lit(_))) {
+            return;
+        }
         node.visit_mut_children_with(self);
     }
 
+    /// Visit tagged templates: css`...` blocks are stylesheets, not class lists
+    fn visit_mut_tagged_tpl(&mut self, node: &mut TaggedTpl) {
+        let is_css = matches!(&*node.tag, Expr::Ident(ident) if ident.sym.as_ref() == "css");
+        if !is_css {
+            node.visit_mut_children_with(self);
+            return;
+        }
+
+        self.scan_css_template(&mut node.tpl, node.span);
+    }
+
+    /// Visit JSX elements: the template in styled-jsx's `<style jsx>{`...`}</style>`
+    /// is a stylesheet, scanned like a css`...` block rather than traced
+    fn visit_mut_jsx_element(&mut self, node: &mut JSXElement) {
+        let is_style = matches!(&node.opening.name, JSXElementName::Ident(ident) if ident.sym.as_ref() == "style");
+        if !is_style {
+            node.visit_mut_children_with(self);
+            return;
+        }
+
+        node.opening.visit_mut_with(self);
+        for child in &mut node.children {
+            if let JSXElementChild::JSXExprContainer(JSXExprContainer { expr: JSXExpr::Expr(expr), .. }) = child {
+                if let Expr::Tpl(tpl) = &mut **expr {
+                    let span = tpl.span;
+                    self.scan_css_template(tpl, span);
+                    continue;
+                }
+            }
+            child.visit_mut_with(self);
+        }
+    }
+
     /// Visit template literals (but not their interpolations)
     fn visit_mut_tpl(&mut self, node: &mut Tpl) {
         // Process only the string parts, not expressions (interpolations)
         for quasi in &mut node.quasis {
             if let Some(cooked) = &quasi.cooked {
                 let cooked_str = cooked.to_string();
-                let processed = self.process_string(&cooked_str);
+                let processed = self.process_string(&cooked_str, quasi.span);
                 quasi.cooked = Some(processed.into());
                 quasi.raw = quasi.cooked.clone().unwrap_or_default(); // Update raw to match
             }
@@ -288,9 +937,15 @@ impl VisitMut for TailwindTransformer {
 
                 // Check if we're in a JSX context
                 let in_jsx = self.context_stack.iter().any(|ctx| {
-                    matches!(ctx, AstContext::FunctionCall(name) if name.contains("jsx") || name.contains("JsxRuntime") || name == "createElement")
+                    matches!(ctx, AstContext::FunctionCall(name) if name.contains("jsx") || is_jsx_factory(name))
                 });
 
+                if let (true, Some(name), Expr::Lit(Lit::Str(value))) = (in_jsx, &prop_name, &*kv.value) {
+                    if self.config.is_class_attribute(name) {
+                        self.check_empty_class_value(name, value);
+                    }
+                }
+
                 // If in JSX and this is a prop, push JSX props context
                 if in_jsx {
                     self.push_context(AstContext::JsxProps(prop_name.clone()));
@@ -304,15 +959,41 @@ impl VisitMut for TailwindTransformer {
                 // Process the key if it's a string (for object literal keys that might be classes)
                 if let PropName::Str(str_key) = &mut kv.key {
                     if self.should_process_string() {
-                        let processed = self.process_string(&str_key.value);
+                        let processed = self.process_string(&str_key.value, str_key.span);
                         str_key.value = processed.into();
                         str_key.raw = None;
                     }
                 }
 
+                // Literal computed keys (`{ ["flex"]: true }`) are class strings too;
+                // dynamic ones (`{ ["bg-" + color]: true }`) can't be resolved statically
+                if let PropName::Computed(computed) = &mut kv.key {
+                    if self.should_process_string() {
+                        let span = computed.span;
+                        match &mut *computed.expr {
+                            Expr::Lit(Lit::Str(str_key)) => {
+                                let processed = self.process_string(&str_key.value, str_key.span);
+                                str_key.value = processed.into();
+                                str_key.raw = None;
+                            }
+                            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl.visit_mut_with(self),
+                            Expr::Bin(BinExpr { op: BinaryOp::Add, .. }) | Expr::Tpl(_) => {
+                                self.warn(span, "dynamic class key cannot be extracted statically");
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
                 // Visit the value
                 kv.value.visit_mut_with(self);
 
+                if let (true, Some(name), Expr::Lit(Lit::Str(value))) = (in_jsx, &prop_name, &mut *kv.value) {
+                    if self.config.is_class_attribute(name) {
+                        self.trim_class_literal(value);
+                    }
+                }
+
                 // Pop the context we pushed
                 if in_jsx || !self.context_stack.iter().any(|ctx| matches!(ctx, AstContext::ObjectLiteral)) {
                     self.pop_context();
@@ -358,7 +1039,8 @@ impl VisitMut for TailwindTransformer {
     fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
         // Determine the function being called
         let func_name = if let Callee::Expr(expr) = &node.callee {
-            match &**expr {
+            let callee = if self.config.minified_input { indirect_callee(expr) } else { &**expr };
+            match callee {
                 Expr::Member(member_expr) => {
                     // Handle member calls like JsxRuntime.jsx
                     if let Expr::Ident(obj_ident) = &*member_expr.obj {
@@ -382,8 +1064,16 @@ impl VisitMut for TailwindTransformer {
             String::new()
         };
 
+        // Module specifiers passed to require() or dynamic import() are never classes
+        if func_name == "require" || matches!(node.callee, Callee::Import(_)) {
+            self.push_context(AstContext::ImportStatement);
+            node.visit_mut_children_with(self);
+            self.pop_context();
+            return;
+        }
+
         // Special handling for JSX function calls
-        if func_name.contains("JsxRuntime") || func_name == "jsx" || func_name == "jsxs" || func_name == "_jsx" || func_name == "createElement" {
+        if is_jsx_factory(&func_name) {
             self.push_context(AstContext::FunctionCall(func_name.clone()));
             
             // Process the JSX props specially
@@ -412,6 +1102,13 @@ impl VisitMut for TailwindTransformer {
         } else if func_name == "join" {
             // For array.join(), process normally
             node.visit_mut_children_with(self);
+        } else if func_name == "Object.assign" {
+            // Object.assign merges props objects: visit the arguments without
+            // pushing a call context so their className properties are handled
+            // exactly like those of a literal props object
+            for arg in node.args.iter_mut() {
+                arg.expr.visit_mut_with(self);
+            }
         } else {
             // For other function calls, push context and visit
             if !func_name.is_empty() {
@@ -462,6 +1159,17 @@ impl VisitMut for TailwindTransformer {
                 import.visit_mut_children_with(self);
                 self.pop_context();
             }
+            // Re-exports and `import x = require(...)` carry module specifiers too
+            ModuleDecl::ExportAll(_) | ModuleDecl::TsImportEquals(_) => {
+                self.push_context(AstContext::ImportStatement);
+                node.visit_mut_children_with(self);
+                self.pop_context();
+            }
+            ModuleDecl::ExportNamed(named) if named.src.is_some() => {
+                self.push_context(AstContext::ImportStatement);
+                named.visit_mut_children_with(self);
+                self.pop_context();
+            }
             _ => node.visit_mut_children_with(self),
         }
     }
@@ -473,21 +1181,40 @@ pub fn transform_source(
     source: &str,
     config: TransformConfig,
 ) -> Result<(String, TransformMetadata)> {
+    // Pathologically nested input would overflow the stack while parsing or visiting
+    let depth = nesting_depth(source);
+    if depth > config.max_nesting_depth {
+        return Ok((
+            source.to_string(),
+            TransformMetadata {
+                classes: vec![],
+                original_count: 0,
+                warnings: vec![format!(
+                    "skipped: brackets nest {} levels deep (limit {})",
+                    depth, config.max_nesting_depth
+                )],
+                locations: IndexMap::new(),
+            },
+        ));
+    }
+
     // Set up SWC components
     let cm: Lrc<SourceMap> = Default::default();
     let fm = cm.new_source_file(FileName::Anon.into(), source.to_string());
 
     let comments = SingleThreadedComments::default();
-    let lexer = Lexer::new(
-        Syntax::Typescript(TsSyntax {
+    // Bundles are already plain JavaScript; skipping TypeScript and comments lexes faster
+    let (syntax, lexer_comments): (Syntax, Option<&dyn Comments>) = if config.minified_input {
+        (Syntax::Es(EsSyntax::default()), None)
+    } else {
+        let syntax = Syntax::Typescript(TsSyntax {
             tsx: true,
             decorators: true,
             ..Default::default()
-        }),
-        EsVersion::latest(),
-        StringInput::from(&*fm),
-        Some(&comments),
-    );
+        });
+        (syntax, Some(&comments))
+    };
+    let lexer = Lexer::new(syntax, EsVersion::latest(), StringInput::from(&*fm), lexer_comments);
 
     let mut parser = Parser::new_from(lexer);
 
@@ -502,6 +1229,8 @@ pub fn transform_source(
                 TransformMetadata {
                     classes: vec![],
                     original_count: 0,
+                    warnings: vec![],
+                    locations: IndexMap::new(),
                 },
             ));
         }
@@ -510,15 +1239,18 @@ pub fn transform_source(
     // Apply transformation
     GLOBALS.set(&Globals::new(), || {
         // Create and apply our transformer
-        let mut transformer = TailwindTransformer::new(config.clone())
+        let mut transformer = TailwindTransformer::new(config.clone(), cm.clone())
             .context("Failed to create transformer")?;
 
         module.visit_mut_with(&mut transformer);
+        if let Some(prefix) = &config.safelist_comment_prefix {
+            transformer.record_comment_safelist(&comments, prefix);
+        }
 
         // Generate the output code
         let mut buf = vec![];
         let mut emitter = Emitter {
-            cfg: CodegenConfig::default(),
+            cfg: CodegenConfig::default().with_minify(config.minified_input),
             cm: cm.clone(),
             comments: Some(&comments),
             wr: JsWriter::new(cm, "\n", &mut buf, None),
@@ -532,6 +1264,8 @@ pub fn transform_source(
         let metadata = TransformMetadata {
             classes: transformer.classes.into_iter().collect(),
             original_count: transformer.total_count,
+            warnings: transformer.warnings,
+            locations: transformer.locations,
         };
 
         Ok((code, metadata))
@@ -928,6 +1662,10 @@ function TestComponent() {
             ("leading-[162.5%]", vec!["leading-[162.5%]"]),
             // Test nested brackets (though rare in Tailwind)
             ("custom-[calc(100%-2rem)]", vec!["custom-[calc(100%-2rem)]"]),
+            // Opacity modifiers, including arbitrary decimal values
+            ("bg-blue-500/50 text-white/[0.3]", vec!["bg-blue-500/50", "text-white/[0.3]"]),
+            // Stray closing brackets don't swallow the following classes
+            ("a] b c", vec!["a]", "b", "c"]),
         ];
 
         for (input, expected) in test_cases {
@@ -939,4 +1677,793 @@ function TestComponent() {
             );
         }
     }
+
+    #[test]
+    fn test_preserved_classes_are_not_obfuscated() {
+        let source = r#"const cls = "active bg-blue-500 p-4";"#;
+
+        let config = TransformConfig {
+            obfuscate: true,
+            preserve_classes: vec!["active".to_string(), "bg-blue-500".to_string()],
+            ..Default::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        // Preserved classes keep their names, the rest is obfuscated as usual
+        let expected = format!("active bg-blue-500 {}", trace_assert("p-4", true));
+        assert!(transformed.contains(&expected), "{}", transformed);
+
+        // Metadata always records the original names
+        assert!(metadata.classes.contains(&"bg-blue-500".to_string()));
+        assert!(metadata.classes.contains(&"p-4".to_string()));
+    }
+
+    #[test]
+    fn test_object_assign_class_names() {
+        let source = r#"
+const props = Object.assign({}, { className: "flex" });
+JsxRuntime.jsx("div", Object.assign({}, base, {
+  className: "p-4",
+  title: "Hello world"
+}));
+JsxRuntime.jsx("span", { ...base, className: "m-2" });
+        "#;
+
+        let config = TransformConfig::default();
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert!(metadata.classes.contains(&"flex".to_string()));
+        assert!(metadata.classes.contains(&"p-4".to_string()));
+        assert!(metadata.classes.contains(&"m-2".to_string()));
+        assert!(transformed.contains(&trace_assert("flex", false)), "{}", transformed);
+        assert!(transformed.contains(&trace_assert("p-4", false)), "{}", transformed);
+
+        // Non-class props merged into JSX props are left alone
+        assert!(!metadata.classes.contains(&"Hello".to_string()));
+    }
+
+    #[test]
+    fn test_module_specifiers_are_not_recorded() {
+        let source = r#"
+import React from "react";
+export * from "./components";
+export { Button } from "./button";
+const runtime = require("react/jsx-runtime");
+const Lazy = import("./lazy");
+const cls = "flex";
+        "#;
+
+        let config = TransformConfig::default();
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        // Only the genuine class string is recorded
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+        assert!(transformed.contains("react/jsx-runtime"), "{}", transformed);
+        assert!(transformed.contains("./components"), "{}", transformed);
+    }
+
+    #[test]
+    fn test_switch_case_class_names() {
+        let source = r#"
+var cls;
+switch (variant) {
+  case "danger":
+    cls = "bg-red-500";
+    break;
+  case "success":
+    cls = "bg-green-500";
+    break;
+  default:
+    cls = "bg-gray-500";
+}
+        "#;
+
+        let config = TransformConfig::default();
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        // Every case body contributes its classes
+        assert_eq!(
+            metadata.classes,
+            vec!["bg-red-500".to_string(), "bg-green-500".to_string(), "bg-gray-500".to_string()]
+        );
+        assert!(transformed.contains(&trace_assert("bg-green-500", false)), "{}", transformed);
+
+        // Case labels are left untouched
+        assert!(transformed.contains("\"danger\""), "{}", transformed);
+    }
+
+    #[test]
+    fn test_opacity_modifiers() {
+        let source = r#"
+const Overlay = () => (
+  <div className="bg-blue-500/50 text-white/[0.3] hover:bg-black/25" />
+);
+        "#;
+
+        let config = TransformConfig::default();
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(
+            metadata.classes,
+            vec![
+                "bg-blue-500/50".to_string(),
+                "text-white/[0.3]".to_string(),
+                "hover:bg-black/25".to_string(),
+            ]
+        );
+        assert!(
+            transformed.contains(&trace_assert("bg-blue-500/50 text-white/[0.3] hover:bg-black/25", false)),
+            "{}",
+            transformed
+        );
+    }
+
+    #[test]
+    fn test_nested_class_helper_calls() {
+        let source = r#"
+const classes = clsx(cn("flex"), twMerge("p-4"));
+const Card = () => JsxRuntime.jsx("div", {
+  className: clsx(cn("m-2"), twMerge("text-sm")),
+  title: "Card title"
+});
+        "#;
+
+        let config = TransformConfig {
+            obfuscate: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        // Every nesting level contributes its string arguments
+        for class in ["flex", "p-4", "m-2", "text-sm"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+            assert!(transformed.contains(&trace_assert(class, true)), "{}", transformed);
+        }
+
+        // Contexts are popped after the nested calls, so sibling props are left alone
+        assert!(transformed.contains("\"Card title\""), "{}", transformed);
+        assert!(!metadata.classes.contains(&"Card".to_string()));
+    }
+
+    #[test]
+    fn test_configured_class_attributes() {
+        let source = r#"
+const Jsx = () => <Card containerClassName="flex" />;
+const Compiled = JsxRuntime.jsx(Card, { containerClassName: "p-4", label: "Other title" });
+        "#;
+
+        let config = TransformConfig {
+            class_attributes: vec!["containerClassName".to_string()],
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string(), "p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_class_attributes_only() {
+        let source = r#"
+const El = () => <my-button part="label" exportparts="icon" data-variant="p-4" buttonClass="flex" />;
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert!(metadata.classes.contains(&"label".to_string()), "{:?}", metadata.classes);
+
+        let config = TransformConfig {
+            class_attributes: vec!["buttonClass".to_string()],
+            class_attributes_only: true,
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+    }
+
+    #[test]
+    fn test_const_assertion_class_array() {
+        let source = r#"
+const CLASSES = ["flex", "p-4"] as const;
+const Box = () => <div className={CLASSES.join(" ")} />;
+        "#;
+
+        let config = TransformConfig::default();
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string(), "p-4".to_string()]);
+        assert!(transformed.contains("as const"), "{}", transformed);
+    }
+
+    #[test]
+    fn test_function_bodies_returning_class_strings() {
+        let source = r#"
+const arrow = () => "flex " + (x ? "p-4" : "m-2");
+const handler = function () {
+  return active ? "bg-blue-500" : "bg-gray-100";
+};
+        "#;
+
+        let config = TransformConfig::default();
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        for class in ["flex", "p-4", "m-2", "bg-blue-500", "bg-gray-100"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+    }
+
+    #[test]
+    fn test_css_template_blocks() {
+        let source = r#"
+const card = css`
+  @apply flex p-4;
+  &:hover {
+    @apply bg-blue-500 !important;
+  }
+  .md\\:grid > .card-title {
+    margin: 0.5rem;
+  }
+`;
+        "#;
+
+        let (transformed, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert_eq!(
+            metadata.classes,
+            vec!["flex".to_string(), "p-4".to_string(), "bg-blue-500".to_string()]
+        );
+        // The stylesheet itself is not rewritten
+        assert!(transformed.contains("margin: 0.5rem;"), "{}", transformed);
+
+        let config = TransformConfig {
+            extract_css_template_selectors: true,
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+        assert!(metadata.classes.contains(&"md:grid".to_string()), "{:?}", metadata.classes);
+        assert!(!metadata.classes.contains(&"card-title".to_string()), "{:?}", metadata.classes);
+    }
+
+    #[test]
+    fn test_inline_css_mode() {
+        let source = r#"
+const Box = () => <div className="p-4 hover:p-8 card">Hello</div>;
+const Plain = () => <span className="p-4" style={{ color: "red" }}>Hi</span>;
+        "#;
+
+        let config = TransformConfig {
+            inline_css: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert!(transformed.contains("style={{"), "{}", transformed);
+        assert!(transformed.contains("padding:"), "{}", transformed);
+        // Variants and custom classes can't be inlined and stay in className
+        assert!(transformed.contains("className=\"hover:p-8 card\""), "{}", transformed);
+        assert!(metadata.classes.contains(&"hover:p-8".to_string()), "{:?}", metadata.classes);
+        // Elements with an existing style attribute are left alone
+        assert!(transformed.contains("className=\"p-4\""), "{}", transformed);
+    }
+
+    #[test]
+    fn test_style_prop_names() {
+        let name_of = |prop: PropOrSpread| match prop {
+            PropOrSpread::Prop(prop) => match *prop {
+                Prop::KeyValue(KeyValueProp { key: PropName::Ident(ident), .. }) => ident.sym.to_string(),
+                Prop::KeyValue(KeyValueProp { key: PropName::Str(s), .. }) => s.value.to_string(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+
+        assert_eq!(name_of(style_prop("background-color", "red")), "backgroundColor");
+        assert_eq!(name_of(style_prop("-webkit-box-orient", "vertical")), "WebkitBoxOrient");
+        assert_eq!(name_of(style_prop("--tw-bg-opacity", "1")), "--tw-bg-opacity");
+    }
+
+    #[test]
+    fn test_chained_ternary_branches() {
+        let source = r#"
+const padding = size === "sm" ? "p-1" : size === "md" ? "p-2" : "p-3";
+const Box = () => <div className={a ? "m-1" : b ? "m-2" : "m-3"} />;
+        "#;
+
+        let config = TransformConfig::default();
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        for class in ["p-1", "p-2", "p-3", "m-1", "m-2", "m-3"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+    }
+
+    #[test]
+    fn test_tsx_generic_components() {
+        let source = r#"
+import React from "react";
+
+interface Props { title: string }
+
+const Card: React.FC<Props> = ({ title }) => <div className="p-4">{title}</div>;
+
+const List = <T,>({ items }: { items: T[] }) => (
+  <ul className="flex gap-2">{items.map((item) => <li key={String(item)}>{String(item)}</li>)}</ul>
+);
+
+function Select<T extends string>(props: { value: T }): JSX.Element {
+  return <select className="rounded-md" value={props.value as T} />;
+}
+        "#;
+
+        let config = TransformConfig::default();
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        for class in ["p-4", "flex", "gap-2", "rounded-md"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+    }
+
+    #[test]
+    fn test_computed_object_keys() {
+        let source = r#"
+const a = clsx({ ["flex"]: true, [`p-4`]: isPadded });
+const b = clsx({
+  ["bg-" + color]: true,
+});
+        "#;
+
+        let config = TransformConfig::default();
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert!(metadata.classes.contains(&"flex".to_string()));
+        assert!(metadata.classes.contains(&"p-4".to_string()));
+        // The dynamic key is reported instead of recording a partial class
+        assert!(!metadata.classes.contains(&"bg-".to_string()), "{:?}", metadata.classes);
+        assert_eq!(metadata.warnings.len(), 1, "{:?}", metadata.warnings);
+        assert!(metadata.warnings[0].starts_with("4:3: dynamic class key"), "{:?}", metadata.warnings);
+    }
+
+    #[test]
+    fn test_configured_class_functions() {
+        let source = r#"
+const el = JsxRuntime.jsx(Tooltip, { label: myClassHelper("p-4"), title: "Hello" });
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert!(!metadata.classes.contains(&"p-4".to_string()), "{:?}", metadata.classes);
+
+        let config = TransformConfig {
+            class_functions: vec!["myClassHelper".to_string()],
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+        assert_eq!(metadata.classes, vec!["p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_filter_boolean_join_idiom() {
+        let source = r#"
+const Button = ({ active }) => JsxRuntime.jsx("button", {
+  className: ["rounded-md", active && "p-4"].filter(Boolean).join(" "),
+  title: ["Save", "changes"].filter(Boolean).join(" ")
+});
+        "#;
+
+        let config = TransformConfig::default();
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["rounded-md".to_string(), "p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_aggressive_mode() {
+        let source = r#"
+const el = JsxRuntime.jsx(Box, { v: "p-4 btn-primary", label: "Hello world" });
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert!(metadata.classes.is_empty(), "{:?}", metadata.classes);
+
+        let config = TransformConfig {
+            aggressive: true,
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+        assert_eq!(metadata.classes, vec!["p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_strip_dead_classnames() {
+        let source = r#"
+const A = () => <div className="definitely-not-a-class">Dead</div>;
+const B = () => <div className="flex">Live</div>;
+        "#;
+
+        let config = TransformConfig {
+            strip_dead_classnames: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert!(!transformed.contains("definitely-not-a-class"), "{}", transformed);
+        assert!(transformed.contains("className=\"flex\""), "{}", transformed);
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_json_classes() {
+        let source = r#"
+const El = () => <div className='["flex","p-4"]' />;
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert!(!metadata.classes.contains(&"flex".to_string()), "{:?}", metadata.classes);
+
+        let config = TransformConfig {
+            parse_json_classes: true,
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string(), "p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_rescript_lookup_object_members() {
+        // ReScript compiles variant-based styling to a lookup table read by member access
+        let source = r#"
+var Size = { sm: "px-2", lg: "px-6 text-lg" };
+function Button(props) {
+  return JsxRuntime.jsx("button", { className: Size[props.size] || Size.sm });
+}
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        for class in ["px-2", "px-6", "text-lg"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+    }
+
+    #[test]
+    fn test_trim_class_whitespace() {
+        let source = r#"
+const El = () => <div className=" flex ">Hi</div>;
+const Compiled = () => jsx("div", { className: " p-4 ", title: " Hi " });
+const cls = " flex " + extra;
+        "#;
+
+        let config = TransformConfig {
+            trim_class_whitespace: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, _) = transform_source(source, config).unwrap();
+
+        assert!(transformed.contains(r#"className="flex""#), "{}", transformed);
+        assert!(transformed.contains(r#"className: "p-4""#), "{}", transformed);
+        assert!(transformed.contains(r#"title: " Hi ""#), "{}", transformed);
+        assert!(transformed.contains(r#"" flex " + extra"#), "{}", transformed);
+    }
+
+    #[test]
+    fn test_sort_class_order() {
+        let source = r#"
+const El = () => <div className="text-white flex p-4">Hi</div>;
+const cls = "text-white flex";
+        "#;
+
+        let config = TransformConfig {
+            sort_class_order: true,
+            ..TransformConfig::default()
+        };
+        let (first, _) = transform_source(source, config.clone()).unwrap();
+        let (second, _) = transform_source(source, config).unwrap();
+
+        assert!(first.contains(r#"className="flex p-4 text-white""#), "{}", first);
+        // Only class attributes are rewritten
+        assert!(first.contains(r#""text-white flex""#), "{}", first);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_safelist_comments() {
+        let source = r#"
+/* tailwind-safelist: bg-red-500 text-white */
+const El = () => <div className="flex">Hi</div>;
+/**
+ * tailwind-safelist: animate-spin
+ */
+// unrelated: p-8
+        "#;
+
+        let config = TransformConfig {
+            safelist_comment_prefix: Some("tailwind-safelist:".to_string()),
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex", "bg-red-500", "text-white", "animate-spin"]);
+        assert_eq!(metadata.locations["bg-red-500"].0, 2);
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert_eq!(metadata.classes, vec!["flex"]);
+    }
+
+    #[test]
+    fn test_directive_prologues_are_not_classes() {
+        let source = r#"
+"use client";
+"use strict";
+
+export function Button() {
+  return <button className="px-4 py-2">Save</button>;
+}
+
+export async function save() {
+  "use server";
+  return "text-sm";
+}
+"#;
+
+        let (transformed, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["px-4", "py-2", "text-sm"]);
+        assert!(transformed.starts_with("\"use client\";\n\"use strict\";"), "{}", transformed);
+    }
+
+    #[test]
+    fn test_rescript_create_element_variadic() {
+        let source = r#"
+function Card(props) {
+  return React.createElementVariadic("div", { className: "flex p-4", title: "Card title" }, props.children);
+}
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string(), "p-4".to_string()]);
+    }
+
+    #[test]
+    fn test_hook_callbacks_returning_classes() {
+        let source = r#"
+function Panel({ active }) {
+  const base = useMemo(() => "flex p-4", []);
+  const tone = useCallback(() => {
+    return active ? "bg-blue-500" : "bg-gray-100";
+  }, [active]);
+  return <div className={`${base} ${tone()}`} />;
+}
+        "#;
+
+        let config = TransformConfig {
+            obfuscate: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        for class in ["flex", "p-4", "bg-blue-500", "bg-gray-100"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+        assert!(transformed.contains(&trace_assert("flex p-4", true)), "{}", transformed);
+    }
+
+    #[test]
+    fn test_nested_arbitrary_grid_class() {
+        let source = r#"
+const Grid = () => <div className="grid grid-cols-[repeat(3,minmax(0,1fr))] gap-4" />;
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        assert!(
+            metadata.classes.contains(&"grid-cols-[repeat(3,minmax(0,1fr))]".to_string()),
+            "{:?}",
+            metadata.classes
+        );
+        assert_eq!(
+            parse_tailwind_classes("grid-cols-[repeat(3, minmax(0, 1fr))] gap-4"),
+            vec!["grid-cols-[repeat(3, minmax(0, 1fr))]", "gap-4"]
+        );
+    }
+
+    #[test]
+    fn test_conditionally_rendered_children() {
+        let source = r#"
+const Jsx = ({ cond }) => <div>{cond ? <span className="p-4" /> : <span className="m-2" />}</div>;
+const Compiled = ({ cond }) => JsxRuntime.jsx("div", {
+  children: cond ? JsxRuntime.jsx("span", { className: "px-2" }) : JsxRuntime.jsx("span", { className: "mx-4" })
+});
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        for class in ["p-4", "m-2", "px-2", "mx-4"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+    }
+
+    #[test]
+    fn test_include_empty_class_attributes() {
+        let source = r#"
+const Jsx = () => <div className="">Empty</div>;
+const Compiled = JsxRuntime.jsx("div", { className: " " });
+const Filled = () => <div className="flex" />;
+        "#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        assert!(metadata.warnings.is_empty(), "{:?}", metadata.warnings);
+
+        let config = TransformConfig {
+            include_empty: true,
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(
+            metadata.warnings,
+            vec![
+                "2:34: empty `className` attribute".to_string(),
+                "3:53: empty `className` attribute".to_string(),
+            ]
+        );
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+    }
+
+    #[test]
+    fn test_shebang_line() {
+        let source = "#!/usr/bin/env node\nconst App = () => <div className=\"flex\" />;\n";
+
+        let (transformed, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+        assert_eq!(metadata.locations["flex"], (2, 34));
+        assert!(transformed.starts_with("#!/usr/bin/env node\n"), "{}", transformed);
+    }
+
+    #[test]
+    fn test_spread_arguments_in_class_functions() {
+        let source = r#"
+const a = clsx(...["flex", "p-4"], "m-2");
+const el = JsxRuntime.jsx("div", { className: clsx(...["grid", "gap-2"]) });
+        "#;
+
+        let config = TransformConfig {
+            class_functions: vec!["clsx".to_string()],
+            ..TransformConfig::default()
+        };
+        let (_, metadata) = transform_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex", "p-4", "m-2", "grid", "gap-2"]);
+    }
+
+    #[test]
+    fn test_deeply_nested_source_is_skipped() {
+        let source = format!(
+            "const cls = \"flex\";\nconst x = {}1{};",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+
+        let (transformed, metadata) = transform_source(&source, TransformConfig::default()).unwrap();
+
+        assert_eq!(transformed, source);
+        assert!(metadata.classes.is_empty(), "{:?}", metadata.classes);
+        assert_eq!(
+            metadata.warnings,
+            vec![format!("skipped: brackets nest 100000 levels deep (limit {})", DEFAULT_MAX_NESTING_DEPTH)]
+        );
+    }
+
+    #[test]
+    fn test_brackets_in_strings_and_comments_are_not_nesting() {
+        let brackets = "(".repeat(1_000);
+        let source = format!(
+            "const a = \"{b}\";\nconst b = '{b}';\nconst c = `{b}${{\"p-4\"}}{b}`;\n// {b}\n/* {b} */\nconst cls = \"flex\";",
+            b = brackets
+        );
+
+        let (_, metadata) = transform_source(&source, TransformConfig::default()).unwrap();
+
+        assert!(metadata.warnings.is_empty(), "{:?}", metadata.warnings);
+        assert!(metadata.classes.contains(&"flex".to_string()), "{:?}", metadata.classes);
+        assert_eq!(nesting_depth("f(`a${g(`b${[1]}`)}`)"), 5);
+    }
+
+    #[test]
+    fn test_minified_bundle() {
+        let component = |i: usize| {
+            format!(
+                "function c{i}(n){{return(0,e.jsx)(\"div\",{{className:\"flex items-center p-4\",children:(0,e.jsxs)(\"span\",{{className:n?\"font-bold\":\"text-sm\",children:[\"Hello\",n]}})}})}}"
+            )
+        };
+        let source = format!(
+            "(()=>{{var e=require(\"react/jsx-runtime\");{}}})();",
+            (0..500).map(component).collect::<String>()
+        );
+        let config = TransformConfig {
+            minified_input: true,
+            ..TransformConfig::default()
+        };
+
+        let started = std::time::Instant::now();
+        let (transformed, metadata) = transform_source(&source, config).unwrap();
+        let elapsed = started.elapsed();
+
+        for class in ["flex", "items-center", "p-4", "font-bold", "text-sm"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+        for not_class in ["div", "span", "Hello", "react/jsx-runtime"] {
+            assert!(!metadata.classes.contains(&not_class.to_string()), "Unexpected class: {}", not_class);
+        }
+        assert_eq!(metadata.original_count, 500 * 5);
+        assert!(!transformed.trim_end().contains('\n'), "output should stay minified");
+        assert!(elapsed.as_secs() < 10, "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_default_props_assignment() {
+        let source = r#"
+function Card(props) {
+  return <div className={props.className}>{props.children}</div>;
+}
+Card.defaultProps = { className: "p-4 flex", title: "Untitled" };
+"#;
+
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+        for class in ["p-4", "flex"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+
+        let config = TransformConfig {
+            obfuscate: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, _) = transform_source(source, config).unwrap();
+        assert!(!transformed.contains("\"p-4 flex\""), "{}", transformed);
+    }
+
+    #[test]
+    fn test_styled_jsx_style_block() {
+        let source = r#"
+export function Button() {
+  return (
+    <button className="btn">
+      Save
+      <style jsx>{`
+        .btn { @apply px-4; }
+      `}</style>
+    </button>
+  );
+}
+"#;
+        let config = TransformConfig {
+            obfuscate: true,
+            ..TransformConfig::default()
+        };
+
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        assert!(metadata.classes.contains(&"px-4".to_string()), "{:?}", metadata.classes);
+        assert!(!metadata.classes.contains(&"@apply".to_string()));
+        assert!(transformed.contains(".btn { @apply px-4; }"), "{}", transformed);
+    }
+
+    #[test]
+    fn test_destructuring_and_parameter_defaults() {
+        let source = r#"
+const Btn = ({ className = "p-4 flex" }) => <button className={className} />;
+function Card(padding = "m-2") { return padding; }
+        "#;
+
+        let config = TransformConfig {
+            obfuscate: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) = transform_source(source, config).unwrap();
+
+        for class in ["p-4", "flex", "m-2"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+        assert!(transformed.contains(&trace_assert("p-4 flex", true)), "{}", transformed);
+        assert!(transformed.contains(&trace_assert("m-2", true)), "{}", transformed);
+    }
 }
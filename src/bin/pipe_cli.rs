@@ -1,18 +1,38 @@
 //! Tailwind CSS extractor CLI with transform and generate modes
 //!
-//! This CLI provides two distinct modes:
+//! This CLI provides the following modes:
 //! 1. transform - Read JS from stdin, transform it using AST transformer, output to stdout, write metadata to file
 //! 2. generate - Read metadata JSON from stdin, generate CSS using tailwind-rs, output to stdout
+//! 3. query - Read metadata files, print which source files use the given classes as JSON
+//! 4. explain - Print the CSS generated for a single class
+//! 5. purge - Drop rules for classes no longer used by the given sources from a stylesheet
 
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use indexmap::{IndexMap, IndexSet};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use encoding_rs::{Encoding, UTF_16LE, UTF_8, WINDOWS_1252};
+use std::collections::HashSet;
 use std::fs;
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
-use tailwind_extractor::{transform_source, TransformConfig};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+use tailwind_extractor::{
+    animation_keyframes, classify_class, dedup_case_insensitive, format_css, generate_css_from_classes,
+    has_known_extension, is_css_export_key, mappings_to_css_export, minify_css, obfuscation_map, purge_css, redundant_variants, requires_css_escape,
+    substitute_tailwind_directives, transform_by_extension, transform_json_source, uses_arbitrary_value,
+    variant_breakdown, ClassClassification, CssOrder, CssStyle, FileSink, GenerateOptions, OutputSink,
+    TransformConfig, DEFAULT_MAX_NESTING_DEPTH,
+};
 use tailwind_rs::TailwindBuilder;
 
+#[cfg(feature = "memory-stats")]
+#[global_allocator]
+static ALLOCATOR: tailwind_extractor::memory::PeakAlloc<std::alloc::System> =
+    tailwind_extractor::memory::PeakAlloc::new(std::alloc::System);
+
 #[derive(Parser)]
 #[command(name = "tailwind-extractor-cli")]
 #[command(about = "Tailwind CSS extractor and transformer CLI", long_about = None)]
@@ -25,34 +45,396 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Transform JavaScript/TypeScript, extracting Tailwind classes
-    Transform {
-        /// Path to write metadata JSON file
-        #[arg(value_name = "METADATA_PATH")]
-        metadata_output: PathBuf,
-        
-        /// Obfuscate Tailwind classes for production
-        #[arg(long)]
-        obfuscate: bool,
-        
-        /// Source file name (optional, for metadata)
-        #[arg(long)]
-        source_file: Option<String>,
-    },
-    
+    Transform(TransformArgs),
+
     /// Generate CSS from metadata JSON
-    Generate {
-        /// Disable preflight CSS
-        #[arg(long = "no-preflight")]
-        no_preflight: bool,
-        
-        /// Minify output CSS
-        #[arg(long)]
-        minify: bool,
+    Generate(GenerateArgs),
+
+    /// Look up which source files use the given classes
+    Query(QueryArgs),
+
+    /// Print the CSS a single class generates
+    Explain(ExplainArgs),
+
+    /// Remove rules for unused classes from an existing stylesheet
+    Purge(PurgeArgs),
+
+    /// Combine metadata from separate runs (e.g. one per monorepo package) into one
+    Aggregate(AggregateArgs),
+}
+
+#[derive(Args)]
+struct TransformArgs {
+    /// Path to write metadata JSON file
+    #[arg(value_name = "METADATA_PATH")]
+    metadata_output: PathBuf,
+
+    /// Obfuscate Tailwind classes for production
+    #[arg(long)]
+    obfuscate: bool,
+
+    /// Obfuscate, but keep each original class next to its obfuscated name
+    #[arg(long)]
+    obfuscate_additive: bool,
+
+    /// Source file name (optional, for metadata)
+    #[arg(long)]
+    source_file: Option<String>,
+
+    /// Record --source-file relative to this directory (e.g. the repository root) so
+    /// metadata is the same on every machine
+    #[arg(long, value_name = "DIR")]
+    relative_to: Option<PathBuf>,
+
+    /// Record a category (layout, spacing, color, ...) and variant kinds for each class
+    #[arg(long)]
+    categorize: bool,
+
+    /// Warn about classes differing only by case and keep just the lowercase spelling
+    #[arg(long)]
+    warn_case_duplicates: bool,
+
+    /// Report classes using arbitrary values (`w-[37px]`) instead of theme tokens
+    #[arg(long)]
+    lint_arbitrary: bool,
+
+    /// Like --lint-arbitrary, but fail when any arbitrary-value class is found
+    #[arg(long)]
+    deny_arbitrary: bool,
+
+    /// Report classes with repeated variants (`hover:hover:`) or stacked breakpoints (`md:lg:`)
+    #[arg(long)]
+    warn_redundant_variants: bool,
+
+    /// Like --warn-redundant-variants, but fail when any such class is found
+    #[arg(long)]
+    deny_redundant_variants: bool,
+
+    /// Record how many classes use each variant prefix (`base`, `md`, `hover`, ...)
+    #[arg(long)]
+    group_by_variant: bool,
+
+    /// Treat stdin as a JSON/JSONC component tree and process `className`/`class` values
+    #[arg(long)]
+    scan_json: bool,
+
+    /// Class that must never be obfuscated (repeatable)
+    #[arg(long = "preserve-class", value_name = "CLASS")]
+    preserve_classes: Vec<String>,
+
+    /// Extra attribute/prop name whose value is a class list, besides `className`/`class` (repeatable)
+    #[arg(long = "class-attribute", value_name = "NAME")]
+    class_attributes: Vec<String>,
+
+    /// Also record utility classes used as selectors inside css`...` template blocks
+    #[arg(long)]
+    css_template_selectors: bool,
+
+    /// Replace static JSX classes with inline `style` objects where possible (e.g. for email)
+    #[arg(long)]
+    inline_css: bool,
+
+    /// Function whose string arguments are always class lists (repeatable)
+    #[arg(long = "treat-function", value_name = "NAME")]
+    class_functions: Vec<String>,
+
+    /// Process every string literal and record whatever generates Tailwind CSS (slow)
+    #[arg(long)]
+    aggressive: bool,
+
+    /// Input is a minified single-line bundle: parse it as plain JavaScript and keep
+    /// the output minified; classes are found through compiled JSX calls
+    #[arg(long, conflicts_with = "aggressive")]
+    minified_input: bool,
+
+    /// Prefix classes that fail to trace with `UNTRACED-` in the output, for debugging
+    #[arg(long)]
+    mark_untraced: bool,
+
+    /// Remove className attributes whose classes generate no CSS at all
+    #[arg(long)]
+    strip_dead_classnames: bool,
+
+    /// Also read class lists from strings holding a JSON array, e.g. `["flex","p-4"]`
+    #[arg(long)]
+    parse_json_classes: bool,
+
+    /// Trim leading/trailing whitespace from JSX className literals, including
+    /// compiled `jsx("div", { className: "..." })` props (concatenated strings keep theirs)
+    #[arg(long)]
+    trim_class_whitespace: bool,
+
+    /// Rewrite JSX className literals in Tailwind's recommended class order, like the
+    /// Prettier Tailwind plugin
+    #[arg(long)]
+    sort_classname_order: bool,
+
+    /// Ignore plain JSX attribute values other than className/class and --class-attribute names
+    #[arg(long)]
+    class_attributes_only: bool,
+
+    /// Warn, with locations, about empty `className=""` attributes (classes are unaffected)
+    #[arg(long)]
+    include_empty: bool,
+
+    /// Also record classes listed in comments starting with PREFIX
+    /// (default `tailwind-safelist:`), e.g. `/* tailwind-safelist: bg-red-500 */`
+    #[arg(long, value_name = "PREFIX", num_args = 0..=1, default_missing_value = "tailwind-safelist:")]
+    safelist_comments: Option<String>,
+
+    /// Skip sources whose brackets nest deeper than this, with a warning
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_NESTING_DEPTH)]
+    max_nesting_depth: usize,
+
+    /// Pass --source-file through untouched unless its extension is a known code or
+    /// front-end extension (.js/.jsx/.ts/.tsx/.mjs/.cjs/.mts/.cts/.json/.svelte/.css/.gjs/.gts)
+    #[arg(long)]
+    strict_extensions: bool,
+
+    /// Exit with an error after writing the outputs if any warning was produced
+    #[arg(long)]
+    fail_on_warnings: bool,
+
+    /// Encoding of the source read from stdin (UTF-8 input may carry a BOM)
+    #[arg(long, value_enum, default_value_t = InputEncoding::Utf8)]
+    input_encoding: InputEncoding,
+
+    /// Record peak heap usage during the transform (requires the `memory-stats` feature)
+    #[arg(long)]
+    report_memory: bool,
+
+    /// Compare the metadata against the existing file instead of writing it; fail if it differs
+    #[arg(long)]
+    check: bool,
+
+    /// Write a JSON report with counts, warnings and timings to this path
+    #[arg(long, value_name = "PATH")]
+    report_json: Option<PathBuf>,
+
+    /// Write `{ obfuscated: { original, sources } }` JSON linking obfuscated names to
+    /// where their classes were found
+    #[arg(long, value_name = "PATH")]
+    provenance: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Disable preflight CSS
+    #[arg(long = "no-preflight")]
+    no_preflight: bool,
+
+    /// Minify output CSS
+    #[arg(long)]
+    minify: bool,
+
+    /// Obfuscate Tailwind classes for production
+    #[arg(long)]
+    obfuscate: bool,
+
+    /// Class that must never be obfuscated (repeatable)
+    #[arg(long = "preserve-class", value_name = "CLASS")]
+    preserve_classes: Vec<String>,
+
+    /// Emit rules for both the readable and the obfuscated selectors, matching
+    /// `transform --obfuscate-additive`
+    #[arg(long, conflicts_with = "obfuscate")]
+    obfuscate_additive: bool,
+
+    /// Reformat the generated CSS (default: as produced by tailwind-rs)
+    #[arg(long, value_enum)]
+    css_style: Option<CssStyle>,
+
+    /// Order of the CSS rules; `canonical` orders utilities the way Tailwind does
+    #[arg(long, value_enum, default_value_t = CssOrder::Bundle)]
+    sort: CssOrder,
+
+    /// Print the CSS as is, or as a JavaScript/TypeScript module default-exporting it
+    #[arg(long, value_enum, default_value_t = CssModule::Css)]
+    css_module: CssModule,
+
+    /// File whose contents are placed before the generated CSS, e.g. a `/*! license */`
+    #[arg(long, value_name = "PATH")]
+    css_prepend: Option<PathBuf>,
+
+    /// File whose contents are placed after the generated CSS, e.g. utility overrides
+    #[arg(long, value_name = "PATH")]
+    css_append: Option<PathBuf>,
+
+    /// Stylesheet template whose `@tailwind base;` and `@tailwind utilities;` directives
+    /// are replaced with the preflight and the generated utilities
+    #[arg(long, value_name = "PATH")]
+    css_entry: Option<PathBuf>,
 
-        /// Obfuscate Tailwind classes for production
-        #[arg(long)]
-        obfuscate: bool,
-    },
+    /// Route classes matching REGEX into NAME.css (repeatable; first match wins)
+    #[arg(long = "bucket", value_name = "NAME=REGEX", value_parser = parse_bucket)]
+    buckets: Vec<Bucket>,
+
+    /// Write one CSS file per bucket, plus `other.css`, into this directory instead of stdout
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "buckets",
+        conflicts_with_all = ["check", "max_css_bytes", "css_module", "report_json", "css_entry", "obfuscate_additive"]
+    )]
+    buckets_dir: Option<PathBuf>,
+
+    /// Pipe the final CSS through this shell command (run with `sh -c`, or `cmd /C` on
+    /// Windows, so quoting and pipes work; e.g. `lightningcss --minify`) and use its
+    /// stdout; on failure the unprocessed CSS is kept
+    #[arg(long, value_name = "COMMAND")]
+    postcss: Option<String>,
+
+    /// Write readable CSS to ORIG_CSS and obfuscated CSS to OBF_CSS instead of stdout
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["ORIG_CSS", "OBF_CSS"],
+        conflicts_with_all = [
+            "check",
+            "max_css_bytes",
+            "css_module",
+            "report_json",
+            "css_entry",
+            "obfuscate",
+            "obfuscate_additive",
+            "buckets_dir",
+        ]
+    )]
+    dual_output: Option<Vec<PathBuf>>,
+
+    /// Write the original-to-obfuscated class mapping to this path
+    #[arg(long, value_name = "PATH")]
+    mapping_output: Option<PathBuf>,
+
+    /// Format of --mapping-output
+    #[arg(long, value_enum, default_value_t = MapFormat::Json)]
+    map_format: MapFormat,
+
+    /// JSON array of classes to always generate, even if no source uses them
+    #[arg(long, value_name = "PATH")]
+    safelist_file: Option<PathBuf>,
+
+    /// Fail if the final CSS is larger than this many bytes
+    #[arg(long, value_name = "N")]
+    max_css_bytes: Option<usize>,
+
+    /// Compare the CSS against this file instead of printing it; fail if it differs
+    #[arg(long, value_name = "CSS_PATH")]
+    check: Option<PathBuf>,
+
+    /// Write a JSON report with counts, CSS size and timings to this path
+    #[arg(long, value_name = "PATH")]
+    report_json: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    /// Metadata JSON files written by `transform`
+    #[arg(value_name = "METADATA_PATH", required = true)]
+    metadata_files: Vec<PathBuf>,
+
+    /// Class to look up (repeatable)
+    #[arg(long = "class", value_name = "CLASS", required = true)]
+    classes: Vec<String>,
+}
+
+#[derive(Args)]
+struct AggregateArgs {
+    /// Metadata JSON files to combine
+    #[arg(value_name = "METADATA_PATH", required = true)]
+    metadata_files: Vec<PathBuf>,
+
+    /// JSON class mapping written by `generate --mapping-output --map-format json`
+    /// (repeatable); mappings that disagree on a class are an error
+    #[arg(long = "mapping", value_name = "PATH")]
+    mappings: Vec<PathBuf>,
+
+    /// Write the combined class mapping to this path
+    #[arg(long, value_name = "PATH", requires = "mappings")]
+    mapping_output: Option<PathBuf>,
+
+    /// Format of --mapping-output
+    #[arg(long, value_enum, default_value_t = MapFormat::Json)]
+    map_format: MapFormat,
+}
+
+#[derive(Args)]
+struct ExplainArgs {
+    /// Class to trace, e.g. `bg-blue-500`
+    #[arg(value_name = "CLASS")]
+    class: String,
+}
+
+#[derive(Args)]
+struct PurgeArgs {
+    /// Stylesheet to purge
+    #[arg(long, value_name = "IN")]
+    css: PathBuf,
+
+    /// Where to write the purged stylesheet (default: stdout)
+    #[arg(long, value_name = "OUT")]
+    output: Option<PathBuf>,
+
+    /// Source files whose classes are kept
+    #[arg(value_name = "SOURCE", required = true)]
+    sources: Vec<PathBuf>,
+}
+
+/// Named CSS output receiving the classes that match its pattern
+#[derive(Debug, Clone)]
+struct Bucket {
+    name: String,
+    pattern: Regex,
+}
+
+/// Name of the bucket receiving classes that match no pattern
+const OTHER_BUCKET: &str = "other";
+
+/// Parse a `NAME=REGEX` bucket definition
+fn parse_bucket(value: &str) -> Result<Bucket, String> {
+    let (name, pattern) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=REGEX, got `{}`", value))?;
+    if name.is_empty() || name == OTHER_BUCKET {
+        return Err(format!("invalid bucket name `{}`", name));
+    }
+    let pattern = Regex::new(pattern).map_err(|e| format!("invalid bucket pattern: {}", e))?;
+    Ok(Bucket {
+        name: name.to_string(),
+        pattern,
+    })
+}
+
+/// Supported encodings for transform input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum InputEncoding {
+    /// UTF-8, honouring a UTF-8 or UTF-16 byte order mark if present
+    Utf8,
+    /// ISO-8859-1 (decoded as windows-1252, per the WHATWG encoding standard)
+    Latin1,
+    /// UTF-16 little endian, with or without a byte order mark
+    Utf16le,
+}
+
+/// Format of a written class mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MapFormat {
+    /// `{ "original": "obfuscated" }`
+    Json,
+    /// A CSS-modules `:export { original: obfuscated; }` block; classes other than
+    /// letters, digits, `-` and `_` (e.g. `hover:p-4`) can't be keys and are left out
+    CssExport,
+}
+
+/// Form of the generated stylesheet on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CssModule {
+    /// Plain CSS
+    Css,
+    /// A module whose default export is the CSS string
+    Js,
+    /// A typed `const` exported as default
+    Ts,
 }
 
 /// Metadata format for class extraction
@@ -64,13 +446,25 @@ struct Metadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "sourceFile")]
     source_file: Option<String>,
+    /// Source files (or metadata paths) combined by `aggregate`
+    #[serde(rename = "sourceFiles", default, skip_serializing_if = "Vec::is_empty")]
+    source_files: Vec<String>,
     /// ISO timestamp of processing
     #[serde(rename = "processedAt")]
     processed_at: String,
     /// Crate version
     version: String,
+    /// Resolved tailwind-rs version, which determines the generated CSS
+    #[serde(rename = "tailwindRsVersion", default, skip_serializing_if = "Option::is_none")]
+    tailwind_rs_version: Option<String>,
     /// Statistics about extraction
     stats: Stats,
+    /// Per-class category breakdown (only with --categorize)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    categories: Option<IndexMap<String, ClassClassification>>,
+    /// Keyframes the generated CSS includes for `animate-*` classes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keyframes: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -81,153 +475,1152 @@ struct Stats {
     /// Count of unique classes
     #[serde(rename = "uniqueCount")]
     unique_count: usize,
+    /// Count of unique classes whose selector needs CSS escaping (e.g. `w-1/2`)
+    #[serde(rename = "escapeRequiredCount", default)]
+    escape_required_count: usize,
+    /// Peak heap bytes allocated above the baseline while transforming (only with --report-memory)
+    #[serde(rename = "peakMemoryBytes", default, skip_serializing_if = "Option::is_none")]
+    peak_memory_bytes: Option<usize>,
+    /// Unique classes per variant prefix (only with --group-by-variant)
+    #[serde(rename = "variantBreakdown", default, skip_serializing_if = "Option::is_none")]
+    variant_breakdown: Option<IndexMap<String, usize>>,
+}
+
+/// Machine-readable summary of a run, written with `--report-json`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    /// Subcommand that produced the report
+    command: &'static str,
+    /// Outcome for each input file
+    files: Vec<FileOutcome>,
+    /// Classes found before deduplication
+    original_class_count: usize,
+    /// Unique classes
+    unique_class_count: usize,
+    /// Size of the generated CSS (generate only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    css_bytes: Option<usize>,
+    /// Warnings, also printed to stderr
+    warnings: Vec<String>,
+    /// Timing and size measurements
+    performance: Performance,
+}
+
+/// What happened to one input file
+#[derive(Debug, Serialize)]
+struct FileOutcome {
+    path: String,
+    status: FileStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum FileStatus {
+    Transformed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Performance {
+    /// Wall-clock time of the run
+    duration_ms: f64,
+    /// Size of the input read from stdin
+    input_bytes: usize,
+    /// Peak heap usage (only with --report-memory)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory_bytes: Option<usize>,
+}
+
+impl Performance {
+    fn since(started: Instant, input_bytes: usize, peak_memory_bytes: Option<usize>) -> Self {
+        Self {
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            input_bytes,
+            peak_memory_bytes,
+        }
+    }
+}
+
+impl Report {
+    /// Report for a transform that passed its input through untouched
+    fn skipped(path: String, reason: String, started: Instant, input_bytes: usize) -> Self {
+        Self {
+            command: "transform",
+            files: vec![FileOutcome {
+                path: path.clone(),
+                status: FileStatus::Skipped,
+                reason: Some(reason.clone()),
+            }],
+            original_class_count: 0,
+            unique_class_count: 0,
+            css_bytes: None,
+            warnings: vec![format!("skipping {}: {}", path, reason)],
+            performance: Performance::since(started, input_bytes, None),
+        }
+    }
+
+    /// Write the report as pretty JSON
+    fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize report")?;
+        fs::write(path, json).with_context(|| format!("Failed to write report to {:?}", path))
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Transform { metadata_output, obfuscate, source_file } => {
-            handle_transform_mode(metadata_output, obfuscate, source_file)
-        }
-        Commands::Generate { no_preflight, obfuscate, minify } => {
-            handle_generate_mode(no_preflight, obfuscate, minify)
-        }
+        Commands::Transform(args) => handle_transform_mode(args),
+        Commands::Generate(args) => handle_generate_mode(args),
+        Commands::Query(args) => handle_query_mode(args),
+        Commands::Explain(args) => handle_explain_mode(args),
+        Commands::Purge(args) => handle_purge_mode(args),
+        Commands::Aggregate(args) => handle_aggregate_mode(args),
+    }
+}
+
+/// Version of the tailwind-rs dependency, recorded by the build script from Cargo.lock
+fn tailwind_rs_version() -> Option<String> {
+    option_env!("TAILWIND_RS_VERSION").map(str::to_string)
+}
+
+/// `path` relative to `base`, or unchanged if it is not inside `base`
+fn relative_path(path: &str, base: &Path) -> String {
+    match Path::new(path).strip_prefix(base) {
+        Ok(relative) => relative.to_string_lossy().into_owned(),
+        Err(_) => path.to_string(),
     }
 }
 
+/// Decode raw transform input using the requested encoding.
+///
+/// Returns `None` if the bytes are not valid in that encoding.
+fn decode_input(bytes: &[u8], encoding: InputEncoding) -> Option<String> {
+    let (encoding, bom_length) = match encoding {
+        InputEncoding::Utf8 => Encoding::for_bom(bytes).unwrap_or((UTF_8, 0)),
+        InputEncoding::Latin1 => (WINDOWS_1252, 0),
+        InputEncoding::Utf16le => (UTF_16LE, if bytes.starts_with(&[0xFF, 0xFE]) { 2 } else { 0 }),
+    };
+
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes[bom_length..])
+        .map(|decoded| decoded.into_owned())
+}
+
+/// Start measuring peak heap usage; returns the baseline to pass to [`peak_memory_since`]
+#[cfg(feature = "memory-stats")]
+fn start_memory_measurement() -> Option<usize> {
+    Some(ALLOCATOR.reset_peak())
+}
+
+#[cfg(not(feature = "memory-stats"))]
+fn start_memory_measurement() -> Option<usize> {
+    eprintln!("Warning: --report-memory requires the `memory-stats` feature; skipping");
+    None
+}
+
+/// Peak heap bytes allocated above `baseline` since the measurement started
+#[cfg(feature = "memory-stats")]
+fn peak_memory_since(baseline: Option<usize>) -> Option<usize> {
+    baseline.map(|baseline| ALLOCATOR.peak_bytes().saturating_sub(baseline))
+}
+
+#[cfg(not(feature = "memory-stats"))]
+fn peak_memory_since(_baseline: Option<usize>) -> Option<usize> {
+    None
+}
+
 /// Transform mode: Read JS from stdin, transform it, output transformed JS and metadata
-fn handle_transform_mode(
-    metadata_output: PathBuf,
-    obfuscate: bool,
-    source_file: Option<String>,
-) -> Result<()> {
+fn handle_transform_mode(mut args: TransformArgs) -> Result<()> {
+    let started = Instant::now();
+    if let Some(base) = &args.relative_to {
+        args.source_file = args.source_file.map(|file| relative_path(&file, base));
+    }
+    let source_name = args.source_file.clone().unwrap_or_else(|| "<stdin>".to_string());
+
     // Read JavaScript from stdin
-    let mut input = String::new();
+    let mut bytes = Vec::new();
     io::stdin()
-        .read_to_string(&mut input)
+        .read_to_end(&mut bytes)
         .context("Failed to read JavaScript from stdin")?;
+
+    // Undecodable input is passed through untouched rather than failing the build
+    let Some(input) = decode_input(&bytes, args.input_encoding) else {
+        let reason = format!("input is not valid {:?}", args.input_encoding);
+        eprintln!("Warning: skipping {}: {}", source_name, reason);
+        io::stdout()
+            .write_all(&bytes)
+            .context("Failed to write JavaScript to stdout")?;
+        let report = Report::skipped(source_name, reason, started, bytes.len());
+        if let Some(path) = &args.report_json {
+            report.write(path)?;
+        }
+        if args.fail_on_warnings {
+            deny_warnings(&report.warnings)?;
+        }
+        return Ok(());
+    };
+
+    // In strict mode, files that aren't code (README.md, notes.txt) are not parsed as JavaScript
+    if args.strict_extensions && args.source_file.as_deref().is_some_and(|path| !has_known_extension(path)) {
+        let reason = "unrecognized file extension".to_string();
+        eprintln!("Note: skipping {}: {}", source_name, reason);
+        io::stdout()
+            .write_all(&bytes)
+            .context("Failed to write JavaScript to stdout")?;
+        if let Some(path) = &args.report_json {
+            let mut report = Report::skipped(source_name, reason, started, bytes.len());
+            // An intentional skip is not a problem worth a warning
+            report.warnings.clear();
+            report.write(path)?;
+        }
+        return Ok(());
+    }
     
     // Configure transformation
     let config = TransformConfig {
-        obfuscate,
+        obfuscate: args.obfuscate || args.obfuscate_additive,
+        obfuscate_additive: args.obfuscate_additive,
         source_maps: false,
+        preserve_classes: args.preserve_classes.clone(),
+        class_attributes: args.class_attributes,
+        extract_css_template_selectors: args.css_template_selectors,
+        inline_css: args.inline_css,
+        class_functions: args.class_functions,
+        aggressive: args.aggressive,
+        mark_untraced: args.mark_untraced,
+        strip_dead_classnames: args.strip_dead_classnames,
+        parse_json_classes: args.parse_json_classes,
+        trim_class_whitespace: args.trim_class_whitespace,
+        sort_class_order: args.sort_classname_order,
+        class_attributes_only: args.class_attributes_only,
+        include_empty: args.include_empty,
+        max_nesting_depth: args.max_nesting_depth,
+        minified_input: args.minified_input,
+        safelist_comment_prefix: args.safelist_comments,
     };
     
-    // Transform the source code using AST transformer
-    let (transformed_js, transform_metadata) = transform_source(&input, config)
-        .context("Failed to transform JavaScript")?;
+    let memory_baseline = if args.report_memory {
+        start_memory_measurement()
+    } else {
+        None
+    };
+
+    // Transform the source code using AST transformer, or the JSON scanner for UI
+    // definitions; Svelte components and stylesheets are picked by extension
+    let (transformed_js, mut transform_metadata) = if args.scan_json {
+        transform_json_source(&input, config).context("Failed to transform JSON")?
+    } else {
+        transform_by_extension(args.source_file.as_deref().unwrap_or_default(), &input, config)
+            .context("Failed to transform source")?
+    };
+    let peak_memory_bytes = peak_memory_since(memory_baseline);
+
+    let mut warnings: Vec<String> = transform_metadata
+        .warnings
+        .iter()
+        .map(|warning| format!("{}:{}", source_name, warning))
+        .collect();
     
     // Write transformed JavaScript to stdout
     io::stdout()
         .write_all(transformed_js.as_bytes())
         .context("Failed to write transformed JavaScript to stdout")?;
     
+    if args.warn_case_duplicates {
+        let (kept, dropped) = dedup_case_insensitive(&transform_metadata.classes);
+        warnings.extend(dropped.iter().map(|(kept, dropped)| {
+            format!(
+                "{}: `{}` and `{}` differ only by case; keeping `{}`",
+                source_name, kept, dropped, kept
+            )
+        }));
+        transform_metadata.classes = kept;
+    }
+
+    let arbitrary_violations: Vec<String> = if args.lint_arbitrary || args.deny_arbitrary {
+        transform_metadata
+            .classes
+            .iter()
+            .filter(|class| uses_arbitrary_value(class))
+            .map(|class| match transform_metadata.locations.get(class) {
+                Some((line, col)) => format!("{}:{}:{}: arbitrary value `{}`", source_name, line, col, class),
+                None => format!("{}: arbitrary value `{}`", source_name, class),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    warnings.extend(arbitrary_violations.iter().cloned());
+
+    let variant_violations: Vec<String> = if args.warn_redundant_variants || args.deny_redundant_variants {
+        transform_metadata
+            .classes
+            .iter()
+            .filter_map(|class| Some((class, redundant_variants(class)?)))
+            .map(|(class, reason)| match transform_metadata.locations.get(class) {
+                Some((line, col)) => format!("{}:{}:{}: `{}`: {}", source_name, line, col, class, reason),
+                None => format!("{}: `{}`: {}", source_name, class, reason),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    warnings.extend(variant_violations.iter().cloned());
+
+    for warning in &warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    if let Some(path) = &args.provenance {
+        let provenance = class_provenance(
+            &transform_metadata.classes,
+            &transform_metadata.locations,
+            &source_name,
+            &args.preserve_classes,
+        );
+        let json = serde_json::to_string_pretty(&provenance).context("Failed to serialize provenance")?;
+        fs::write(path, json).with_context(|| format!("Failed to write provenance to {:?}", path))?;
+    }
+
     // Prepare metadata
     let unique_count = transform_metadata.classes.len();
+    let escape_required_count = transform_metadata
+        .classes
+        .iter()
+        .filter(|class| requires_css_escape(class))
+        .count();
+    let variant_breakdown = args
+        .group_by_variant
+        .then(|| variant_breakdown(&transform_metadata.classes));
+    let categories = args.categorize.then(|| {
+        transform_metadata
+            .classes
+            .iter()
+            .map(|class| (class.clone(), classify_class(class)))
+            .collect()
+    });
+    let keyframes = animation_keyframes(&transform_metadata.classes);
     let metadata = Metadata {
         classes: transform_metadata.classes,
-        source_file,
+        source_file: args.source_file,
+        source_files: Vec::new(),
         processed_at: chrono::Utc::now().to_rfc3339(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        tailwind_rs_version: tailwind_rs_version(),
         stats: Stats {
             original_count: transform_metadata.original_count,
             unique_count,
+            escape_required_count,
+            peak_memory_bytes,
+            variant_breakdown,
         },
+        categories,
+        keyframes,
     };
+
+    if let Some(path) = &args.report_json {
+        let report = Report {
+            command: "transform",
+            files: vec![FileOutcome {
+                path: source_name,
+                status: FileStatus::Transformed,
+                reason: None,
+            }],
+            original_class_count: metadata.stats.original_count,
+            unique_class_count: metadata.stats.unique_count,
+            css_bytes: None,
+            warnings: warnings.clone(),
+            performance: Performance::since(started, bytes.len(), peak_memory_bytes),
+        };
+        report.write(path)?;
+    }
+
+    if args.deny_arbitrary && !arbitrary_violations.is_empty() {
+        bail!(
+            "{} class(es) use arbitrary values; use theme tokens instead",
+            arbitrary_violations.len()
+        );
+    }
+
+    if args.deny_redundant_variants && !variant_violations.is_empty() {
+        bail!("{} class(es) have redundant variants", variant_violations.len());
+    }
     
-    // Write metadata to file
-    let metadata_json = serde_json::to_string_pretty(&metadata)
-        .context("Failed to serialize metadata")?;
-    
-    fs::write(&metadata_output, metadata_json)
-        .with_context(|| format!("Failed to write metadata to {:?}", metadata_output))?;
-    
+    if args.check {
+        check_metadata_up_to_date(&args.metadata_output, &metadata)?;
+    } else {
+        // Write metadata to file
+        let metadata_json = serde_json::to_string_pretty(&metadata)
+            .context("Failed to serialize metadata")?;
+
+        let sink = FileSink {
+            css_path: None,
+            manifest_path: Some(args.metadata_output),
+        };
+        sink.write_manifest(metadata_json.as_bytes())?;
+    }
+
+    if args.fail_on_warnings {
+        deny_warnings(&warnings)?;
+    }
+
     Ok(())
 }
 
+/// Origin of an obfuscated class name (for --provenance)
+#[derive(Debug, Serialize, Deserialize)]
+struct Provenance {
+    /// Class as written in the source
+    original: String,
+    /// `file:line` locations, or just the file when the front end records no positions
+    sources: Vec<String>,
+}
+
+/// Key each class's obfuscated name to its original name and where it was found
+fn class_provenance(
+    classes: &[String],
+    locations: &IndexMap<String, (usize, usize)>,
+    source_name: &str,
+    preserve_classes: &[String],
+) -> IndexMap<String, Provenance> {
+    let options = GenerateOptions {
+        preserve_classes: preserve_classes.to_vec(),
+        ..GenerateOptions::default()
+    };
+    obfuscation_map(classes, &options)
+        .into_iter()
+        .map(|(original, obfuscated)| {
+            let source = match locations.get(&original) {
+                Some((line, _)) => format!("{}:{}", source_name, line),
+                None => source_name.to_string(),
+            };
+            let provenance = Provenance {
+                original,
+                sources: vec![source],
+            };
+            (obfuscated, provenance)
+        })
+        .collect()
+}
+
+/// Fail with a summary if any warnings were produced (for --fail-on-warnings)
+fn deny_warnings(warnings: &[String]) -> Result<()> {
+    if warnings.is_empty() {
+        return Ok(());
+    }
+    bail!(
+        "{} warning(s) with --fail-on-warnings:\n  {}",
+        warnings.len(),
+        warnings.join("\n  ")
+    );
+}
+
 /// Generate mode: Read metadata JSON from stdin, generate CSS and output to stdout
-fn handle_generate_mode(no_preflight: bool, obfuscate: bool, minify: bool) -> Result<()> {
+fn handle_generate_mode(args: GenerateArgs) -> Result<()> {
+    let started = Instant::now();
+
     // Read metadata JSON from stdin
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .context("Failed to read metadata JSON from stdin")?;
     
-    // If input is empty, output empty CSS
-    if input.trim().is_empty() {
-        return Ok(());
+    // Parse metadata; empty input means no extracted classes
+    let (mut classes, original_count) = if input.trim().is_empty() {
+        (Vec::new(), 0)
+    } else {
+        let metadata: Metadata = serde_json::from_str(&input)
+            .context("Failed to parse metadata JSON")?;
+        (metadata.classes, metadata.stats.original_count)
+    };
+
+    if let Some(path) = &args.safelist_file {
+        merge_safelist(&mut classes, read_safelist(path)?);
     }
     
-    // Parse metadata
-    let metadata: Metadata = serde_json::from_str(&input)
-        .context("Failed to parse metadata JSON")?;
-    
     // If no classes, output empty CSS
-    if metadata.classes.is_empty() {
-        return Ok(());
+    if classes.is_empty() {
+        return emit_css(&css_module_source("", args.css_module), args.check.as_deref());
     }
     
+    if let Some(path) = &args.mapping_output {
+        write_obfuscation_map(path, &classes, &args.preserve_classes, args.map_format)?;
+    }
+
+    if let Some(dir) = &args.buckets_dir {
+        return write_css_buckets(dir, &classes, &args);
+    }
+
+    if let Some(paths) = &args.dual_output {
+        return write_dual_css(&paths[0], &paths[1], &classes, &args);
+    }
+
     // Generate CSS using tailwind-rs
-    let css = generate_tailwind_css(metadata.classes, no_preflight, minify, obfuscate)?;
+    let mut css = match &args.css_entry {
+        Some(path) => generate_from_entry(path, &classes, &args)?,
+        None if args.obfuscate_additive => generate_additive_css(&classes, &args)?,
+        None => generate_tailwind_css(
+            classes.clone(),
+            args.no_preflight,
+            false,
+            args.obfuscate,
+            &args.preserve_classes,
+            args.sort,
+        )?,
+    };
+
+    css = finish_css(css, &args)?;
+
+    if let Some(path) = &args.report_json {
+        let report = Report {
+            command: "generate",
+            files: Vec::new(),
+            original_class_count: original_count,
+            unique_class_count: classes.len(),
+            css_bytes: Some(css.len()),
+            warnings: Vec::new(),
+            performance: Performance::since(started, input.len(), None),
+        };
+        report.write(path)?;
+    }
+
+    if let Some(max_bytes) = args.max_css_bytes {
+        enforce_css_budget(&css, &classes, max_bytes)?;
+    }
     
     // Write CSS to stdout
-    io::stdout()
-        .write_all(css.as_bytes())
-        .context("Failed to write CSS to stdout")?;
-    
+    emit_css(&css_module_source(&css, args.css_module), args.check.as_deref())
+}
+
+/// Group classes by the first bucket whose pattern matches, in bucket order.
+///
+/// Every bucket appears in the result, followed by `other` for unmatched classes.
+fn assign_buckets(classes: &[String], buckets: &[Bucket]) -> IndexMap<String, Vec<String>> {
+    let mut assigned: IndexMap<String, Vec<String>> = buckets
+        .iter()
+        .map(|bucket| (bucket.name.clone(), Vec::new()))
+        .chain(std::iter::once((OTHER_BUCKET.to_string(), Vec::new())))
+        .collect();
+
+    for class in classes {
+        let name = buckets
+            .iter()
+            .find(|bucket| bucket.pattern.is_match(class))
+            .map_or(OTHER_BUCKET, |bucket| bucket.name.as_str());
+        assigned[name].push(class.clone());
+    }
+
+    assigned
+}
+
+/// Write `<bucket>.css` for every bucket into `dir`; preflight only goes into `other.css`
+fn write_css_buckets(dir: &Path, classes: &[String], args: &GenerateArgs) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+
+    for (name, classes) in assign_buckets(classes, &args.buckets) {
+        let no_preflight = args.no_preflight || name != OTHER_BUCKET;
+        let css = generate_tailwind_css(
+            classes,
+            no_preflight,
+            false,
+            args.obfuscate,
+            &args.preserve_classes,
+            args.sort,
+        )?;
+        let css = finish_css(css, args)?;
+
+        let sink = FileSink {
+            css_path: Some(dir.join(format!("{}.css", name))),
+            manifest_path: None,
+        };
+        sink.write_css(css.as_bytes())?;
+    }
+
     Ok(())
 }
 
-/// Generate Tailwind CSS for the given classes
-fn generate_tailwind_css(
-    classes: Vec<String>,
-    no_preflight: bool,
-    _minify: bool, // Note: minify isn't directly supported by tailwind-rs yet
-    obfuscate: bool, // Note: minify isn't directly supported by tailwind-rs yet
-) -> Result<String> {
-    let mut builder = TailwindBuilder::default();
-    
-    // Configure preflight
-    builder.preflight.disable = no_preflight;
-    
-    // Process each class through the builder
-    for class in &classes {
-        // Try to trace the class - silently ignore failures for unknown classes
-        let _ = builder.trace(class, obfuscate);
+/// Write the readable and the obfuscated stylesheet for the same classes
+fn write_dual_css(original: &Path, obfuscated: &Path, classes: &[String], args: &GenerateArgs) -> Result<()> {
+    for (path, obfuscate) in [(original, false), (obfuscated, true)] {
+        let css = generate_tailwind_css(
+            classes.to_vec(),
+            args.no_preflight,
+            false,
+            obfuscate,
+            &args.preserve_classes,
+            args.sort,
+        )?;
+        let css = finish_css(css, args)?;
+
+        let sink = FileSink {
+            css_path: Some(path.to_path_buf()),
+            manifest_path: None,
+        };
+        sink.write_css(css.as_bytes())?;
     }
-    
-    // Generate the CSS bundle
-    match builder.bundle() {
-        Ok(css_string) => {
-            // TODO: If minify is true, we could post-process the CSS here
-            // For now, return as-is since tailwind-rs doesn't have built-in minification
-            Ok(css_string)
-        }
-        Err(e) => {
-            // Log warning to stderr and return empty CSS
-            eprintln!("Warning: CSS generation failed: {}", e);
-            Ok(String::new())
+
+    Ok(())
+}
+
+/// Readable CSS followed by the obfuscated rules (without a second preflight)
+fn generate_additive_css(classes: &[String], args: &GenerateArgs) -> Result<String> {
+    let readable = generate_tailwind_css(
+        classes.to_vec(),
+        args.no_preflight,
+        false,
+        false,
+        &args.preserve_classes,
+        args.sort,
+    )?;
+    let obfuscated =
+        generate_tailwind_css(classes.to_vec(), true, false, true, &args.preserve_classes, args.sort)?;
+    Ok(readable + &obfuscated)
+}
+
+/// Read a safelist file containing a JSON array of class names
+fn read_safelist(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read safelist {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Safelist {:?} is not a JSON array of strings", path))
+}
+
+/// Append safelisted classes that were not already extracted
+fn merge_safelist(classes: &mut Vec<String>, safelist: Vec<String>) {
+    for class in safelist {
+        if !classes.contains(&class) {
+            classes.push(class);
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-    #[test]
-    fn test_metadata_serialization() {
-        let metadata = Metadata {
-            classes: vec!["bg-blue-500".to_string(), "text-white".to_string()],
-            source_file: Some("test.js".to_string()),
+/// Write the original-to-obfuscated class mapping for `classes`
+fn write_obfuscation_map(
+    path: &Path,
+    classes: &[String],
+    preserve_classes: &[String],
+    format: MapFormat,
+) -> Result<()> {
+    let options = GenerateOptions {
+        preserve_classes: preserve_classes.to_vec(),
+        ..GenerateOptions::default()
+    };
+    write_mapping(path, &obfuscation_map(classes, &options), format)
+}
+
+/// Write a class mapping as pretty JSON or a CSS-modules `:export` block
+fn write_mapping(path: &Path, mapping: &IndexMap<String, String>, format: MapFormat) -> Result<()> {
+    let content = match format {
+        MapFormat::Json => serde_json::to_string_pretty(mapping).context("Failed to serialize class mapping")?,
+        MapFormat::CssExport => {
+            let skipped: Vec<&str> = mapping
+                .keys()
+                .filter(|class| !is_css_export_key(class))
+                .map(String::as_str)
+                .collect();
+            if !skipped.is_empty() {
+                eprintln!(
+                    "Warning: {} class(es) can't be CSS-modules `:export` keys and were left out of {:?}: {}",
+                    skipped.len(),
+                    path,
+                    skipped.join(", ")
+                );
+            }
+            mappings_to_css_export(mapping)
+        }
+    };
+    fs::write(path, content).with_context(|| format!("Failed to write class mapping to {:?}", path))
+}
+
+/// Run `command` through the shell (`sh -c`, or `cmd /C` on Windows) with the CSS
+/// on stdin and return its stdout.
+///
+/// If the command can't be run, exits unsuccessfully or prints invalid UTF-8, a
+/// warning is printed and the CSS is returned unchanged.
+fn postprocess_css(css: String, command: &str) -> String {
+    let run = || -> Result<String> {
+        if command.trim().is_empty() {
+            bail!("empty command");
+        }
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        let mut child = shell
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("failed to start")?;
+
+        // Write from a separate thread so a command that streams output can't deadlock
+        let mut stdin = child.stdin.take().context("stdin not captured")?;
+        let input = css.clone();
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+        let output = child.wait_with_output().context("failed to wait")?;
+        writer
+            .join()
+            .map_err(|_| anyhow::anyhow!("stdin writer panicked"))?
+            .context("failed to write CSS")?;
+        if !output.status.success() {
+            bail!("exited with {}", output.status);
+        }
+        String::from_utf8(output.stdout).context("output is not UTF-8")
+    };
+
+    match run() {
+        Ok(processed) => processed,
+        Err(err) => {
+            eprintln!("Warning: --postcss `{}` failed ({:#}); keeping unprocessed CSS", command, err);
+            css
+        }
+    }
+}
+
+/// Finish generated, unminified CSS: apply --css-style to it, surround it with the
+/// prepend/append files, then minify the whole stylesheet, so appended rules are
+/// minified too and `/*!` comments in prepended files survive, and finally run --postcss
+fn finish_css(mut css: String, args: &GenerateArgs) -> Result<String> {
+    if let Some(style) = args.css_style {
+        css = format_css(&css, style);
+    }
+    css = wrap_css(css, args.css_prepend.as_deref(), args.css_append.as_deref())?;
+    if args.minify {
+        css = minify_css(&css);
+    }
+    if let Some(command) = &args.postcss {
+        css = postprocess_css(css, command);
+    }
+    Ok(css)
+}
+
+/// Surround the generated CSS with the contents of the prepend/append files
+fn wrap_css(css: String, prepend: Option<&Path>, append: Option<&Path>) -> Result<String> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("Failed to read CSS from {:?}", path))
+    };
+
+    let mut parts = Vec::with_capacity(3);
+    if let Some(path) = prepend {
+        parts.push(read(path)?);
+    }
+    parts.push(css);
+    if let Some(path) = append {
+        parts.push(read(path)?);
+    }
+
+    Ok(parts
+        .iter()
+        .map(|part| part.trim_end_matches('\n'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n")
+}
+
+/// Fill the `@tailwind` directives of an entry stylesheet with generated CSS
+fn generate_from_entry(path: &Path, classes: &[String], args: &GenerateArgs) -> Result<String> {
+    let template =
+        fs::read_to_string(path).with_context(|| format!("Failed to read CSS entry from {:?}", path))?;
+
+    let base = if args.no_preflight {
+        String::new()
+    } else {
+        generate_tailwind_css(Vec::new(), false, false, false, &[], args.sort)?
+    };
+    let utilities = generate_tailwind_css(
+        classes.to_vec(),
+        true,
+        false,
+        args.obfuscate,
+        &args.preserve_classes,
+        args.sort,
+    )?;
+
+    Ok(substitute_tailwind_directives(&template, &base, &utilities))
+}
+
+/// Wrap the CSS in a module exporting it as a template literal; backslashes, backticks
+/// and `${` are escaped so the exported string equals `css`
+fn css_module_source(css: &str, module: CssModule) -> String {
+    let literal = || {
+        let escaped = css.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${");
+        format!("`{}`", escaped)
+    };
+    match module {
+        CssModule::Css => css.to_string(),
+        CssModule::Js => format!("export default {};\n", literal()),
+        CssModule::Ts => format!("const css: string = {};\nexport default css;\n", literal()),
+    }
+}
+
+/// Print the CSS, or with `--check` compare it against the existing file
+fn emit_css(css: &str, check: Option<&Path>) -> Result<()> {
+    match check {
+        Some(path) => check_css_up_to_date(path, css),
+        None => FileSink::default().write_css(css.as_bytes()),
+    }
+}
+
+/// Fail with a short diff summary if the file at `path` doesn't contain exactly `css`
+fn check_css_up_to_date(path: &Path, css: &str) -> Result<()> {
+    let existing = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read existing CSS from {:?}", path))?;
+    if existing == css {
+        return Ok(());
+    }
+
+    let existing_lines: Vec<&str> = existing.lines().collect();
+    let new_lines: Vec<&str> = css.lines().collect();
+    let first_difference = existing_lines
+        .iter()
+        .zip(&new_lines)
+        .position(|(old, new)| old != new)
+        .unwrap_or(existing_lines.len().min(new_lines.len()))
+        + 1;
+
+    bail!(
+        "{:?} is out of date: first difference at line {} ({} lines on disk, {} lines generated)",
+        path,
+        first_difference,
+        existing_lines.len(),
+        new_lines.len()
+    )
+}
+
+/// Fail with the added/removed classes if the metadata at `path` differs from `metadata`.
+///
+/// `processedAt` changes on every run and is ignored.
+fn check_metadata_up_to_date(path: &Path, metadata: &Metadata) -> Result<()> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read existing metadata from {:?}", path))?;
+    let mut existing: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse metadata JSON from {:?}", path))?;
+    let mut current = serde_json::to_value(metadata).context("Failed to serialize metadata")?;
+
+    for value in [&mut existing, &mut current] {
+        if let Some(object) = value.as_object_mut() {
+            object.remove("processedAt");
+        }
+    }
+    if existing == current {
+        return Ok(());
+    }
+
+    let existing_classes: Vec<String> = existing
+        .get("classes")
+        .and_then(|classes| serde_json::from_value(classes.clone()).ok())
+        .unwrap_or_default();
+    let added: Vec<&str> = metadata
+        .classes
+        .iter()
+        .filter(|class| !existing_classes.contains(class))
+        .map(String::as_str)
+        .collect();
+    let removed: Vec<&str> = existing_classes
+        .iter()
+        .filter(|class| !metadata.classes.contains(class))
+        .map(String::as_str)
+        .collect();
+
+    bail!(
+        "{:?} is out of date: added classes [{}], removed classes [{}]",
+        path,
+        added.join(", "),
+        removed.join(", ")
+    )
+}
+
+/// Number of largest classes named when the CSS budget is exceeded
+const BUDGET_REPORT_CLASSES: usize = 5;
+
+/// Fail if the CSS exceeds `max_bytes`, naming the classes that contribute the most
+fn enforce_css_budget(css: &str, classes: &[String], max_bytes: usize) -> Result<()> {
+    if css.len() <= max_bytes {
+        return Ok(());
+    }
+
+    // Size each class by the CSS it generates on its own
+    let mut contributions: Vec<(usize, &String)> = classes
+        .iter()
+        .map(|class| {
+            let size = generate_tailwind_css(vec![class.clone()], true, false, false, &[], CssOrder::Bundle)
+                .map_or(0, |css| css.len());
+            (size, class)
+        })
+        .collect();
+    contributions.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let largest = contributions
+        .iter()
+        .take(BUDGET_REPORT_CLASSES)
+        .map(|(size, class)| format!("{} ({} bytes)", class, size))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    bail!(
+        "Generated CSS is {} bytes, exceeding the budget of {} bytes by {} bytes; largest classes: {}",
+        css.len(),
+        max_bytes,
+        css.len() - max_bytes,
+        largest
+    )
+}
+
+/// Query mode: Load metadata files and print the source files using each requested class as JSON
+fn handle_query_mode(args: QueryArgs) -> Result<()> {
+    let entries = read_metadata_files(args.metadata_files)?;
+
+    let results = query_classes(&entries, &args.classes);
+
+    let json = serde_json::to_string_pretty(&results).context("Failed to serialize query results")?;
+    println!("{}", json);
+
+    Ok(())
+}
+
+/// Aggregate mode: Print the combined metadata of several runs
+fn handle_aggregate_mode(args: AggregateArgs) -> Result<()> {
+    let entries = read_metadata_files(args.metadata_files)?;
+
+    let mut mappings = Vec::with_capacity(args.mappings.len());
+    for path in args.mappings {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read class mapping from {:?}", path))?;
+        let mapping: IndexMap<String, String> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse class mapping JSON from {:?}", path))?;
+        mappings.push((path, mapping));
+    }
+    let mapping = merge_mappings(&mappings)?;
+
+    if let Some(path) = &args.mapping_output {
+        write_mapping(path, &mapping, args.map_format)?;
+    }
+
+    let json = serde_json::to_string_pretty(&aggregate_metadata(&entries))
+        .context("Failed to serialize metadata")?;
+    println!("{}", json);
+
+    Ok(())
+}
+
+/// Read and parse metadata JSON files, keeping their paths
+fn read_metadata_files(paths: Vec<PathBuf>) -> Result<Vec<(PathBuf, Metadata)>> {
+    let mut entries = Vec::with_capacity(paths.len());
+    for path in paths {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read metadata from {:?}", path))?;
+        let metadata: Metadata = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse metadata JSON from {:?}", path))?;
+        entries.push((path, metadata));
+    }
+    Ok(entries)
+}
+
+/// Union the classes of several metadata files, summing their occurrence counts.
+///
+/// Each entry is recorded in `sourceFiles` by its source file, or by the metadata
+/// path when it has none.
+fn aggregate_metadata(entries: &[(PathBuf, Metadata)]) -> Metadata {
+    let mut classes: IndexSet<String> = IndexSet::new();
+    let mut source_files: IndexSet<String> = IndexSet::new();
+    let mut original_count = 0;
+    for (path, metadata) in entries {
+        original_count += metadata.stats.original_count;
+        classes.extend(metadata.classes.iter().cloned());
+        if metadata.source_files.is_empty() {
+            source_files.insert(
+                metadata
+                    .source_file
+                    .clone()
+                    .unwrap_or_else(|| path.display().to_string()),
+            );
+        } else {
+            source_files.extend(metadata.source_files.iter().cloned());
+        }
+    }
+    let classes: Vec<String> = classes.into_iter().collect();
+
+    Metadata {
+        source_file: None,
+        source_files: source_files.into_iter().collect(),
+        processed_at: chrono::Utc::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        tailwind_rs_version: tailwind_rs_version(),
+        stats: Stats {
+            original_count,
+            unique_count: classes.len(),
+            escape_required_count: classes.iter().filter(|class| requires_css_escape(class)).count(),
+            peak_memory_bytes: None,
+            variant_breakdown: None,
+        },
+        categories: None,
+        keyframes: animation_keyframes(&classes),
+        classes,
+    }
+}
+
+/// Combine class mappings, failing if two of them obfuscate a class differently
+fn merge_mappings(mappings: &[(PathBuf, IndexMap<String, String>)]) -> Result<IndexMap<String, String>> {
+    let mut merged: IndexMap<String, (String, &Path)> = IndexMap::new();
+    for (path, mapping) in mappings {
+        for (class, obfuscated) in mapping {
+            match merged.get(class) {
+                Some((existing, existing_path)) if existing != obfuscated => bail!(
+                    "Conflicting mappings for `{}`: `{}` in {:?} but `{}` in {:?}",
+                    class,
+                    existing,
+                    existing_path,
+                    obfuscated,
+                    path
+                ),
+                Some(_) => {}
+                None => {
+                    merged.insert(class.clone(), (obfuscated.clone(), path.as_path()));
+                }
+            }
+        }
+    }
+    Ok(merged.into_iter().map(|(class, (obfuscated, _))| (class, obfuscated)).collect())
+}
+
+/// Explain mode: Print the CSS rule generated for one class
+fn handle_explain_mode(args: ExplainArgs) -> Result<()> {
+    println!("{}", explain_class(&args.class));
+    Ok(())
+}
+
+/// Purge mode: Drop rules for classes that none of the sources use
+fn handle_purge_mode(args: PurgeArgs) -> Result<()> {
+    let mut used = HashSet::new();
+    for path in &args.sources {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read source from {:?}", path))?;
+        let (_, metadata) =
+            transform_by_extension(&path.to_string_lossy(), &source, TransformConfig::default())
+                .with_context(|| format!("Failed to extract classes from {:?}", path))?;
+        used.extend(metadata.classes);
+    }
+
+    let css = fs::read_to_string(&args.css)
+        .with_context(|| format!("Failed to read CSS from {:?}", args.css))?;
+
+    let sink = FileSink {
+        css_path: args.output,
+        manifest_path: None,
+    };
+    sink.write_css(purge_css(&css, &used).as_bytes())
+}
+
+/// Trace a single class without preflight and describe the CSS it produces
+fn explain_class(class: &str) -> String {
+    let mut builder = TailwindBuilder::default();
+    builder.preflight.disable = true;
+
+    let css = match builder.trace(class, false) {
+        Ok(_) => builder.bundle().unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    if css.trim().is_empty() {
+        format!("`{}` is not a recognized Tailwind class; it is passed through unchanged", class)
+    } else {
+        css.trim().to_string()
+    }
+}
+
+/// Map each requested class to the source files whose metadata contains it.
+///
+/// Files are identified by their recorded source file, falling back to the
+/// metadata path when the transform was run without `--source-file`.
+fn query_classes(
+    entries: &[(PathBuf, Metadata)],
+    classes: &[String],
+) -> IndexMap<String, Vec<String>> {
+    classes
+        .iter()
+        .map(|class| {
+            let files = entries
+                .iter()
+                .filter(|(_, metadata)| metadata.classes.contains(class))
+                .map(|(path, metadata)| {
+                    metadata
+                        .source_file
+                        .clone()
+                        .unwrap_or_else(|| path.display().to_string())
+                })
+                .collect();
+            (class.clone(), files)
+        })
+        .collect()
+}
+
+/// Generate Tailwind CSS for the given classes
+fn generate_tailwind_css(
+    classes: Vec<String>,
+    no_preflight: bool,
+    minify: bool,
+    obfuscate: bool,
+    preserve_classes: &[String],
+    order: CssOrder,
+) -> Result<String> {
+    let options = GenerateOptions {
+        no_preflight,
+        minify,
+        obfuscate,
+        preserve_classes: preserve_classes.to_vec(),
+        order,
+    };
+    generate_css_from_classes(&classes, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tailwind_extractor::transform_source;
+
+    /// Parse `generate` flags the way the CLI does
+    fn generate_args(argv: &[&str]) -> GenerateArgs {
+        let cli = Cli::parse_from(["tailwind-extractor-cli", "generate"].iter().chain(argv).copied());
+        let Commands::Generate(args) = cli.command else {
+            panic!("expected generate command");
+        };
+        args
+    }
+
+    /// Metadata recording `classes` once each, as `transform` writes it for `source_file`
+    fn metadata(source_file: Option<&str>, classes: &[&str]) -> Metadata {
+        Metadata {
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+            source_file: source_file.map(str::to_string),
+            source_files: Vec::new(),
             processed_at: "2024-01-01T00:00:00Z".to_string(),
             version: "0.1.0".to_string(),
+            tailwind_rs_version: None,
             stats: Stats {
-                original_count: 3,
-                unique_count: 2,
+                original_count: classes.len(),
+                unique_count: classes.len(),
+                escape_required_count: 0,
+                peak_memory_bytes: None,
+                variant_breakdown: None,
             },
-        };
+            categories: None,
+            keyframes: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_metadata_serialization() {
+        let mut metadata = metadata(Some("test.js"), &["bg-blue-500", "text-white"]);
+        metadata.stats.original_count = 3;
         
         let json = serde_json::to_string(&metadata).unwrap();
         let parsed: Metadata = serde_json::from_str(&json).unwrap();
@@ -237,28 +1630,550 @@ mod tests {
         assert_eq!(parsed.stats.unique_count, 2);
     }
     
+    #[test]
+    fn test_metadata_records_tailwind_rs_version() {
+        let mut metadata = metadata(None, &["flex"]);
+        metadata.tailwind_rs_version = tailwind_rs_version();
+
+        let json: serde_json::Value = serde_json::to_value(&metadata).unwrap();
+
+        let version = json["tailwindRsVersion"].as_str().unwrap();
+        assert!(!version.is_empty());
+    }
+
+    #[test]
+    fn test_metadata_categories_serialization() {
+        let mut metadata = metadata(None, &["bg-blue-500", "md:flex"]);
+        metadata.categories = Some(metadata.classes.iter().map(|c| (c.clone(), classify_class(c))).collect());
+
+        let json: serde_json::Value = serde_json::to_value(&metadata).unwrap();
+
+        assert_eq!(json["categories"]["bg-blue-500"]["category"], "color");
+        assert_eq!(json["categories"]["md:flex"]["category"], "layout");
+        assert_eq!(json["categories"]["md:flex"]["variants"][0], "responsive");
+    }
+
     #[test]
     fn test_generate_css_from_metadata() {
-        let metadata = Metadata {
-            classes: vec![
-                "bg-blue-500".to_string(),
-                "text-white".to_string(),
-                "p-4".to_string(),
-            ],
-            source_file: None,
-            processed_at: chrono::Utc::now().to_rfc3339(),
-            version: "0.1.0".to_string(),
-            stats: Stats {
-                original_count: 3,
-                unique_count: 3,
-            },
-        };
+        let metadata = metadata(None, &["bg-blue-500", "text-white", "p-4"]);
         
-        let css = generate_tailwind_css(metadata.classes, true, false).unwrap();
+        let css = generate_tailwind_css(metadata.classes, true, false, false, &[], CssOrder::Bundle).unwrap();
         
         // Should contain CSS for the classes
         assert!(!css.is_empty());
         // With no-preflight, shouldn't contain reset styles
         assert!(!css.contains("html"));
     }
+
+    #[test]
+    fn test_generate_css_keeps_preserved_selectors() {
+        let classes = vec!["active".to_string(), "bg-blue-500".to_string(), "p-4".to_string()];
+        let preserve = vec!["bg-blue-500".to_string()];
+
+        let css = generate_tailwind_css(classes, true, false, true, &preserve, CssOrder::Bundle).unwrap();
+
+        // The preserved utility keeps its readable selector
+        assert!(css.contains(".bg-blue-500"), "{}", css);
+        // Other utilities are still obfuscated
+        assert!(!css.contains(".p-4"), "{}", css);
+    }
+
+    #[test]
+    fn test_generate_css_minify() {
+        let classes = vec!["p-4".to_string(), "bg-blue-500".to_string(), "md:flex".to_string()];
+
+        let expanded = generate_tailwind_css(classes.clone(), true, false, false, &[], CssOrder::Bundle).unwrap();
+        let minified = generate_tailwind_css(classes, true, true, false, &[], CssOrder::Bundle).unwrap();
+
+        assert!(!minified.contains("  "), "{}", minified);
+        assert!(!minified.contains('\n'), "{}", minified);
+        assert!(minified.len() < expanded.len(), "{} vs {}", minified.len(), expanded.len());
+    }
+
+    #[test]
+    fn test_generate_css_keeps_class_order() {
+        let classes = vec!["mx-4".to_string(), "ml-2".to_string()];
+
+        let css = generate_tailwind_css(classes, true, false, false, &[], CssOrder::Bundle).unwrap();
+
+        // `ml-2` must follow `mx-4` to override its left margin
+        let position = |selector: &str| css.find(selector).unwrap_or_else(|| panic!("{} in {}", selector, css));
+        assert!(position(".mx-4") < position(".ml-2"), "{}", css);
+    }
+
+    #[test]
+    fn test_decode_utf16le_input() {
+        let source = r#"const Box = () => <div className="flex">Hello</div>;"#;
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(source.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+
+        let decoded = decode_input(&bytes, InputEncoding::Utf16le).unwrap();
+        assert_eq!(decoded, source);
+
+        let (_, metadata) = transform_source(&decoded, TransformConfig::default()).unwrap();
+        assert_eq!(metadata.classes, vec!["flex".to_string()]);
+    }
+
+    #[test]
+    fn test_decode_input_rejects_invalid_bytes() {
+        // A lone 0xE9 is invalid UTF-8 but decodes as Latin-1
+        let bytes = b"const a = \"p-4 caf\xE9\";";
+
+        assert!(decode_input(bytes, InputEncoding::Utf8).is_none());
+        assert_eq!(
+            decode_input(bytes, InputEncoding::Latin1).unwrap(),
+            "const a = \"p-4 caf\u{e9}\";"
+        );
+    }
+
+    #[test]
+    fn test_query_classes_across_files() {
+        let entries = vec![
+            (PathBuf::from("a.json"), metadata(Some("src/Header.jsx"), &["flex", "p-4"])),
+            (PathBuf::from("b.json"), metadata(Some("src/Footer.jsx"), &["flex", "m-2"])),
+            (PathBuf::from("c.json"), metadata(Some("src/Card.jsx"), &["m-2"])),
+        ];
+
+        let results = query_classes(&entries, &["flex".to_string(), "grid".to_string()]);
+
+        assert_eq!(results["flex"], vec!["src/Header.jsx", "src/Footer.jsx"]);
+        assert!(results["grid"].is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_package_metadata() {
+        let mut web = metadata(Some("packages/web"), &["flex", "w-1/2"]);
+        web.stats.original_count = 5;
+        let mut admin = metadata(None, &["flex", "animate-spin"]);
+        admin.stats.original_count = 3;
+        let entries = vec![
+            (PathBuf::from("web.json"), web),
+            (PathBuf::from("admin.json"), admin),
+        ];
+
+        let combined = aggregate_metadata(&entries);
+
+        assert_eq!(combined.classes, vec!["flex", "w-1/2", "animate-spin"]);
+        assert_eq!(combined.source_files, vec!["packages/web", "admin.json"]);
+        assert_eq!(combined.stats.original_count, 8);
+        assert_eq!(combined.stats.unique_count, 3);
+        assert_eq!(combined.stats.escape_required_count, 1);
+        assert_eq!(combined.keyframes, vec!["spin"]);
+
+        let mapping = |pairs: &[(&str, &str)]| -> IndexMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let web = (PathBuf::from("web-map.json"), mapping(&[("flex", "a1"), ("w-1/2", "b2")]));
+        let admin = (PathBuf::from("admin-map.json"), mapping(&[("flex", "a1"), ("animate-spin", "c3")]));
+        let merged = merge_mappings(&[web.clone(), admin]).unwrap();
+        assert_eq!(merged.len(), 3);
+
+        let conflicting = (PathBuf::from("other-map.json"), mapping(&[("flex", "z9")]));
+        let err = merge_mappings(&[web, conflicting]).unwrap_err().to_string();
+        assert!(err.contains("`flex`"), "{}", err);
+        assert!(err.contains("web-map.json") && err.contains("other-map.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_css_budget_exceeded() {
+        let classes = vec!["p-4".to_string(), "bg-blue-500".to_string()];
+        let css = generate_tailwind_css(classes.clone(), true, false, false, &[], CssOrder::Bundle).unwrap();
+
+        assert!(enforce_css_budget(&css, &classes, css.len()).is_ok());
+
+        let error = enforce_css_budget(&css, &classes, 10).unwrap_err().to_string();
+        assert!(
+            error.contains(&format!("{} bytes, exceeding the budget of 10 bytes by {} bytes", css.len(), css.len() - 10)),
+            "{}",
+            error
+        );
+        assert!(error.contains("bg-blue-500"), "{}", error);
+    }
+
+    #[test]
+    fn test_check_detects_stale_css() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tailwind.css");
+        fs::write(&path, ".p-4 {\n  padding: 1rem;\n}\n").unwrap();
+
+        assert!(check_css_up_to_date(&path, ".p-4 {\n  padding: 1rem;\n}\n").is_ok());
+
+        let error = check_css_up_to_date(&path, ".p-4 {\n  padding: 2rem;\n}\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("out of date: first difference at line 2"), "{}", error);
+
+        // The stale file is left untouched
+        assert_eq!(fs::read_to_string(&path).unwrap(), ".p-4 {\n  padding: 1rem;\n}\n");
+    }
+
+    #[test]
+    fn test_check_metadata_ignores_timestamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metadata.json");
+        let existing = metadata(Some("src/App.jsx"), &["flex", "p-4"]);
+        fs::write(&path, serde_json::to_string_pretty(&existing).unwrap()).unwrap();
+
+        let mut rerun = metadata(Some("src/App.jsx"), &["flex", "p-4"]);
+        rerun.processed_at = "2024-06-01T00:00:00Z".to_string();
+        assert!(check_metadata_up_to_date(&path, &rerun).is_ok());
+
+        let mut changed = metadata(Some("src/App.jsx"), &["flex", "m-2"]);
+        changed.processed_at = "2024-06-01T00:00:00Z".to_string();
+        let error = check_metadata_up_to_date(&path, &changed).unwrap_err().to_string();
+        assert!(error.contains("added classes [m-2], removed classes [p-4]"), "{}", error);
+    }
+
+    #[test]
+    fn test_wrap_css_with_prepend_and_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let license = dir.path().join("license.css");
+        let overrides = dir.path().join("overrides.css");
+        fs::write(&license, "/*! (c) Example Corp */\n").unwrap();
+        fs::write(&overrides, ".btn { padding: 0; }\n").unwrap();
+
+        let css = generate_tailwind_css(vec!["p-4".to_string()], true, false, false, &[], CssOrder::Bundle).unwrap();
+        let wrapped = wrap_css(css, Some(&license), Some(&overrides)).unwrap();
+
+        assert!(wrapped.starts_with("/*! (c) Example Corp */\n"), "{}", wrapped);
+        assert!(wrapped.ends_with(".btn { padding: 0; }\n"), "{}", wrapped);
+        assert!(wrapped.contains("padding"), "{}", wrapped);
+    }
+
+    #[test]
+    fn test_minify_after_prepend_and_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let license = dir.path().join("license.css");
+        let overrides = dir.path().join("overrides.css");
+        fs::write(&license, "/*! (c) Example Corp */\n").unwrap();
+        fs::write(&overrides, ".btn {\n  padding: 0;\n}\n").unwrap();
+
+        let args = generate_args(&[
+            "--minify",
+            "--css-prepend",
+            license.to_str().unwrap(),
+            "--css-append",
+            overrides.to_str().unwrap(),
+        ]);
+        let css = generate_tailwind_css(vec!["p-4".to_string()], true, false, false, &[], CssOrder::Bundle).unwrap();
+        let finished = finish_css(css, &args).unwrap();
+
+        assert!(finished.starts_with("/*! (c) Example Corp */"), "{}", finished);
+        assert!(finished.contains(".btn{padding:0}"), "{}", finished);
+        assert!(!finished.contains("\n  "), "{}", finished);
+    }
+
+    #[test]
+    fn test_css_buckets() {
+        let buckets = vec![
+            parse_bucket("typography=^text-").unwrap(),
+            parse_bucket("color=^bg-").unwrap(),
+        ];
+        let classes = vec!["text-lg".to_string(), "bg-blue-500".to_string(), "p-4".to_string()];
+
+        let assigned = assign_buckets(&classes, &buckets);
+        assert_eq!(assigned["typography"], vec!["text-lg"]);
+        assert_eq!(assigned["color"], vec!["bg-blue-500"]);
+        assert_eq!(assigned["other"], vec!["p-4"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let license = dir.path().join("license.css");
+        fs::write(&license, "/*! (c) Example Corp */\n").unwrap();
+        let args = generate_args(&[
+            "--no-preflight",
+            "--minify",
+            "--css-prepend",
+            license.to_str().unwrap(),
+            "--bucket",
+            "typography=^text-",
+            "--bucket",
+            "color=^bg-",
+        ]);
+        write_css_buckets(dir.path(), &classes, &args).unwrap();
+
+        let typography = fs::read_to_string(dir.path().join("typography.css")).unwrap();
+        let color = fs::read_to_string(dir.path().join("color.css")).unwrap();
+        assert!(typography.contains("font-size"), "{}", typography);
+        assert!(!typography.contains("background"), "{}", typography);
+        assert!(color.contains("background"), "{}", color);
+        assert!(!color.contains("font-size"), "{}", color);
+        assert!(dir.path().join("other.css").exists());
+        for css in [&typography, &color] {
+            assert!(css.starts_with("/*! (c) Example Corp */"), "{}", css);
+            assert!(!css.contains('\n'), "{}", css);
+        }
+
+        let conflicting = Cli::try_parse_from([
+            "tailwind-extractor-cli",
+            "generate",
+            "--bucket",
+            "typography=^text-",
+            "--buckets-dir",
+            "dist",
+            "--check",
+            "dist/app.css",
+        ]);
+        assert!(conflicting.is_err());
+
+        assert!(parse_bucket("no-pattern").is_err());
+        assert!(parse_bucket("other=^p-").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_postcss_command() {
+        let css = ".p-4 {\n  padding: 1rem;\n}\n".to_string();
+
+        assert_eq!(postprocess_css(css.clone(), "cat"), css);
+        // Quoted arguments reach the command intact
+        assert_eq!(
+            postprocess_css(css.clone(), "sed 's/padding: 1rem/padding: 2rem/'"),
+            ".p-4 {\n  padding: 2rem;\n}\n"
+        );
+        // Failures fall back to the unprocessed CSS
+        assert_eq!(postprocess_css(css.clone(), "false"), css);
+        assert_eq!(postprocess_css(css.clone(), "definitely-not-a-command-xyz"), css);
+    }
+
+    #[test]
+    fn test_dual_output_with_mapping() {
+        let classes = vec!["bg-blue-500".to_string()];
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("orig.css");
+        let obfuscated = dir.path().join("obf.css");
+        let mapping = dir.path().join("mapping.json");
+
+        let args = generate_args(&[
+            "--no-preflight",
+            "--minify",
+            "--dual-output",
+            original.to_str().unwrap(),
+            obfuscated.to_str().unwrap(),
+        ]);
+        write_dual_css(&original, &obfuscated, &classes, &args).unwrap();
+        write_obfuscation_map(&mapping, &classes, &[], MapFormat::Json).unwrap();
+
+        let original_css = fs::read_to_string(&original).unwrap();
+        let obfuscated_css = fs::read_to_string(&obfuscated).unwrap();
+        let map: IndexMap<String, String> = serde_json::from_str(&fs::read_to_string(&mapping).unwrap()).unwrap();
+        let mapped = &map["bg-blue-500"];
+
+        assert_ne!(mapped, "bg-blue-500");
+        assert!(original_css.contains(".bg-blue-500"), "{}", original_css);
+        assert!(!obfuscated_css.contains(".bg-blue-500"), "{}", obfuscated_css);
+        assert!(obfuscated_css.contains(&format!(".{}", mapped)), "{}", obfuscated_css);
+        assert!(!original_css.contains('\n') && !obfuscated_css.contains('\n'));
+
+        for flag in ["--obfuscate-additive", "--check=dist/app.css", "--css-entry=src/app.css"] {
+            let conflicting = Cli::try_parse_from([
+                "tailwind-extractor-cli",
+                "generate",
+                "--dual-output",
+                "orig.css",
+                "obf.css",
+                flag,
+            ]);
+            assert!(conflicting.is_err(), "{}", flag);
+        }
+    }
+
+    #[test]
+    fn test_safelist_file_adds_unused_classes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("safelist.json");
+        fs::write(&path, r#"["animate-pulse", "p-4"]"#).unwrap();
+
+        let mut classes = vec!["p-4".to_string()];
+        merge_safelist(&mut classes, read_safelist(&path).unwrap());
+        assert_eq!(classes, vec!["p-4", "animate-pulse"]);
+
+        let css = generate_tailwind_css(classes, true, false, false, &[], CssOrder::Bundle).unwrap();
+        assert!(css.contains(".animate-pulse"), "{}", css);
+
+        fs::write(&path, r#"{"classes": []}"#).unwrap();
+        assert!(read_safelist(&path).is_err());
+    }
+
+    #[test]
+    fn test_class_provenance_links_obfuscated_names() {
+        let source = "export const App = () => (\n  <div className=\"p-4 bg-blue-500\">\n    <span className=\"font-bold\" />\n  </div>\n);";
+        let (_, metadata) = transform_source(source, TransformConfig::default()).unwrap();
+
+        let provenance = class_provenance(&metadata.classes, &metadata.locations, "src/App.tsx", &[]);
+
+        assert_eq!(provenance.len(), 3);
+        for (obfuscated, entry) in &provenance {
+            assert_ne!(obfuscated, &entry.original);
+            assert!(metadata.classes.contains(&entry.original), "{:?}", entry);
+            assert!(!entry.sources.is_empty());
+        }
+        let bold = provenance.values().find(|entry| entry.original == "font-bold").unwrap();
+        assert_eq!(bold.sources, vec!["src/App.tsx:3"]);
+    }
+
+    #[test]
+    fn test_css_entry_fills_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = dir.path().join("app.css");
+        fs::write(&entry, "@tailwind base;\n.card { color: red; }\n@tailwind utilities;\n").unwrap();
+        let classes = vec!["p-4".to_string()];
+
+        let args = generate_args(&["--css-entry", entry.to_str().unwrap()]);
+        let css = generate_from_entry(&entry, &classes, &args).unwrap();
+
+        let preflight = generate_tailwind_css(Vec::new(), false, false, false, &[], CssOrder::Bundle).unwrap();
+        let utilities = generate_tailwind_css(classes, true, false, false, &[], CssOrder::Bundle).unwrap();
+        assert!(!preflight.trim().is_empty());
+        assert!(utilities.contains(".p-4"), "{}", utilities);
+        assert_eq!(
+            css,
+            format!("{}\n.card {{ color: red; }}\n{}\n", preflight.trim_end_matches('\n'), utilities.trim_end_matches('\n'))
+        );
+    }
+
+    #[test]
+    fn test_css_module_exports_the_css() {
+        use swc_core::common::{sync::Lrc, FileName, SourceMap};
+        use swc_core::ecma::ast::{Expr, ModuleDecl, ModuleItem};
+        use swc_core::ecma::parser::{parse_file_as_module, Syntax};
+
+        let css = generate_tailwind_css(
+            vec!["hover:bg-blue-500".to_string(), "w-[10px]".to_string()],
+            true,
+            false,
+            false,
+            &[],
+            CssOrder::Bundle,
+        )
+        .unwrap()
+            + "/* `${not} an expression` */\n";
+        assert!(css.contains('\\'), "{}", css);
+
+        let module_source = css_module_source(&css, CssModule::Js);
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon.into(), module_source.clone());
+        let module = parse_file_as_module(&fm, Syntax::default(), Default::default(), None, &mut vec![])
+            .unwrap_or_else(|err| panic!("invalid module {:?}:\n{}", err, module_source));
+
+        let [ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export))] = module.body.as_slice() else {
+            panic!("expected a single default export:\n{}", module_source);
+        };
+        let Expr::Tpl(tpl) = &*export.expr else {
+            panic!("expected a template literal:\n{}", module_source);
+        };
+        assert!(tpl.exprs.is_empty());
+        assert_eq!(tpl.quasis[0].cooked.as_ref().unwrap().to_string(), css);
+
+        assert!(css_module_source(&css, CssModule::Ts).starts_with("const css: string = `"));
+        assert_eq!(css_module_source(&css, CssModule::Css), css);
+    }
+
+    #[test]
+    fn test_obfuscate_additive_keeps_original_classes() {
+        let config = TransformConfig {
+            obfuscate: true,
+            obfuscate_additive: true,
+            ..TransformConfig::default()
+        };
+        let (transformed, metadata) =
+            transform_source(r#"const el = <div className="bg-blue-500 my-widget" />;"#, config).unwrap();
+        let mapping = obfuscation_map(&metadata.classes, &GenerateOptions::default());
+        let mapped = &mapping["bg-blue-500"];
+
+        assert_ne!(mapped, "bg-blue-500");
+        assert!(
+            transformed.contains(&format!("\"bg-blue-500 {} my-widget\"", mapped)),
+            "{}",
+            transformed
+        );
+
+        let args = generate_args(&["--no-preflight", "--obfuscate-additive"]);
+        let css = generate_additive_css(&["bg-blue-500".to_string()], &args).unwrap();
+        assert!(css.contains(".bg-blue-500"), "{}", css);
+        assert!(css.contains(&format!(".{}", mapped)), "{}", css);
+    }
+
+    #[test]
+    fn test_relative_source_paths() {
+        let root = Path::new("/home/ci/checkout");
+
+        assert_eq!(relative_path("/home/ci/checkout/src/App.jsx", root), "src/App.jsx");
+        assert_eq!(relative_path("/home/ci/checkout-old/src/App.jsx", root), "/home/ci/checkout-old/src/App.jsx");
+        assert_eq!(relative_path("src/App.jsx", root), "src/App.jsx");
+
+        let cli = Cli::parse_from([
+            "tailwind-extractor-cli",
+            "transform",
+            "meta.json",
+            "--source-file",
+            "/home/ci/checkout/src/App.jsx",
+            "--relative-to",
+            "/home/ci/checkout",
+        ]);
+        let Commands::Transform(args) = cli.command else {
+            panic!("expected transform command");
+        };
+        assert_eq!(args.relative_to.as_deref(), Some(root));
+    }
+
+    #[test]
+    fn test_report_for_skipped_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+
+        let report = Report::skipped(
+            "src/legacy.js".to_string(),
+            "input is not valid Utf8".to_string(),
+            Instant::now(),
+            2048,
+        );
+        report.write(&path).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["files"][0]["status"], "skipped");
+        assert_eq!(json["warnings"][0], "skipping src/legacy.js: input is not valid Utf8");
+        assert_eq!(json["performance"]["inputBytes"], 2048);
+        assert!(json["performance"]["durationMs"].is_number());
+        assert!(json.get("cssBytes").is_none());
+    }
+
+    #[test]
+    fn test_fail_on_warnings_for_skipped_file() {
+        let report = Report::skipped(
+            "src/legacy.js".to_string(),
+            "input is not valid Utf8".to_string(),
+            Instant::now(),
+            2048,
+        );
+
+        let err = deny_warnings(&report.warnings).unwrap_err();
+        assert!(err.to_string().contains("skipping src/legacy.js"), "{}", err);
+        assert!(deny_warnings(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_explain_class() {
+        let explanation = explain_class("p-4");
+        assert!(explanation.contains("padding"), "{}", explanation);
+
+        let explanation = explain_class("not-a-class");
+        assert!(explanation.contains("not a recognized"), "{}", explanation);
+    }
+
+    #[cfg(feature = "memory-stats")]
+    #[test]
+    fn test_peak_memory_reported_after_transforms() {
+        let sources = [
+            r#"const Header = () => <header className="flex items-center p-4">Title</header>;"#,
+            r#"const Footer = () => <footer className="bg-gray-100 text-sm">Footer</footer>;"#,
+        ];
+
+        let baseline = start_memory_measurement();
+        for source in sources {
+            transform_source(source, TransformConfig::default()).unwrap();
+        }
+
+        let peak = peak_memory_since(baseline);
+        assert!(matches!(peak, Some(bytes) if bytes > 0), "{:?}", peak);
+    }
 }
@@ -0,0 +1,841 @@
+//! Helpers for inspecting individual Tailwind class names
+//!
+//! These functions operate on a single class token as produced by the extractor.
+//! They never touch the TailwindBuilder, so they are cheap enough to run over
+//! every extracted class when building metadata.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Broad utility category of a Tailwind class, used for metadata breakdowns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassCategory {
+    /// Display, positioning, sizing, flexbox and grid utilities
+    Layout,
+    /// Padding, margin, gap and space-between utilities
+    Spacing,
+    /// Utilities whose value is a color (`bg-blue-500`, `text-white/50`)
+    Color,
+    /// Font, text sizing/alignment and other text utilities
+    Typography,
+    /// Border width, radius, ring and outline utilities
+    Border,
+    /// Shadows, opacity, transitions, transforms and filters
+    Effect,
+    /// Anything not covered above, including custom classes
+    Other,
+}
+
+/// Kind of variant modifier prefixed to a class (`md:`, `hover:`, `group-hover:`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariantKind {
+    /// Breakpoint variants such as `sm:`, `md:` or `max-lg:`
+    Responsive,
+    /// Pseudo-class and pseudo-element variants such as `hover:` or `before:`
+    State,
+    /// Parent-state variants such as `group-hover:` or `group-focus/item:`
+    Group,
+    /// Sibling-state variants such as `peer-checked:`
+    Peer,
+    /// The `dark:` color scheme variant
+    Dark,
+    /// Any other variant (arbitrary variants, `print:`, `motion-safe:`, ...)
+    Other,
+}
+
+/// Classification of a single class: its utility category and variant stack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassClassification {
+    /// Category of the underlying utility, ignoring variants
+    pub category: ClassCategory,
+    /// Kinds of the variants applied to the utility, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantKind>,
+}
+
+const RESPONSIVE_VARIANTS: &[&str] = &["sm", "md", "lg", "xl", "2xl"];
+
+const STATE_VARIANTS: &[&str] = &[
+    "hover", "focus", "focus-within", "focus-visible", "active", "visited", "target",
+    "disabled", "enabled", "checked", "indeterminate", "required", "invalid", "valid",
+    "placeholder-shown", "read-only", "empty", "first", "last", "only", "odd", "even",
+    "first-of-type", "last-of-type", "before", "after", "placeholder", "file", "marker",
+    "selection", "first-line", "first-letter", "backdrop", "open",
+];
+
+const COLOR_NAMES: &[&str] = &[
+    "inherit", "current", "transparent", "black", "white", "slate", "gray", "zinc",
+    "neutral", "stone", "red", "orange", "amber", "yellow", "lime", "green", "emerald",
+    "teal", "cyan", "sky", "blue", "indigo", "violet", "purple", "fuchsia", "pink", "rose",
+];
+
+/// Prefixes whose value may be a color; anything else falls back to the given category
+const COLOR_PREFIXES: &[(&str, ClassCategory)] = &[
+    ("bg-", ClassCategory::Other),
+    ("text-", ClassCategory::Typography),
+    ("border-", ClassCategory::Border),
+    ("ring-", ClassCategory::Border),
+    ("outline-", ClassCategory::Border),
+    ("divide-", ClassCategory::Border),
+    ("decoration-", ClassCategory::Typography),
+    ("shadow-", ClassCategory::Effect),
+    ("fill-", ClassCategory::Other),
+    ("stroke-", ClassCategory::Other),
+    ("from-", ClassCategory::Other),
+    ("via-", ClassCategory::Other),
+    ("to-", ClassCategory::Other),
+    ("accent-", ClassCategory::Other),
+    ("caret-", ClassCategory::Other),
+    ("placeholder-", ClassCategory::Other),
+];
+
+const LAYOUT_PREFIXES: &[&str] = &[
+    "flex", "grid", "block", "inline", "hidden", "container", "contents", "table", "flow-root",
+    "static", "fixed", "absolute", "relative", "sticky", "inset-", "top-", "right-",
+    "bottom-", "left-", "start-", "end-", "z-", "w-", "h-", "min-w-", "max-w-", "min-h-",
+    "max-h-", "size-", "overflow-", "overscroll-", "justify-", "items-", "content-", "self-",
+    "place-", "order-", "col-", "row-", "auto-cols-", "auto-rows-", "basis-", "grow",
+    "shrink", "float-", "clear-", "object-", "aspect-", "columns-", "box-", "isolate",
+    "visible", "invisible", "collapse",
+];
+
+const SPACING_PREFIXES: &[&str] = &[
+    "p-", "px-", "py-", "pt-", "pr-", "pb-", "pl-", "ps-", "pe-", "m-", "mx-", "my-", "mt-",
+    "mr-", "mb-", "ml-", "ms-", "me-", "gap-", "space-",
+];
+
+const TYPOGRAPHY_PREFIXES: &[&str] = &[
+    "font-", "leading-", "tracking-", "uppercase", "lowercase", "capitalize", "normal-case",
+    "italic", "not-italic", "underline", "overline", "line-through", "no-underline",
+    "truncate", "whitespace-", "break-", "list-", "indent-", "align-", "antialiased",
+    "subpixel-antialiased", "line-clamp-", "hyphens-",
+];
+
+const BORDER_PREFIXES: &[&str] = &["border", "rounded", "ring", "outline", "divide"];
+
+const EFFECT_PREFIXES: &[&str] = &[
+    "shadow", "opacity-", "blur", "brightness-", "contrast-", "grayscale", "invert",
+    "saturate-", "sepia", "drop-shadow", "filter", "backdrop-", "mix-blend-", "bg-blend-",
+    "transition", "duration-", "ease-", "delay-", "animate-", "transform", "scale-",
+    "rotate-", "translate-", "skew-", "origin-",
+];
+
+/// Utility families in Tailwind's property order (as its core plugins are registered).
+/// A family name matches itself and any `name-...` utility; the longest name wins.
+pub(crate) const UTILITY_ORDER: &[&[&str]] = &[
+    &["container"],
+    &["sr-only", "not-sr-only"],
+    &["pointer-events"],
+    &["visible", "invisible", "collapse"],
+    &["static", "fixed", "absolute", "relative", "sticky"],
+    &["inset", "start", "end", "top", "right", "bottom", "left"],
+    &["isolate", "isolation"],
+    &["z"],
+    &["order"],
+    &["col"],
+    &["row"],
+    &["float"],
+    &["clear"],
+    &["m", "mx", "my", "mt", "mr", "mb", "ml", "ms", "me"],
+    &["box"],
+    &["line-clamp"],
+    &[
+        "block", "inline", "inline-block", "inline-flex", "inline-grid", "inline-table", "flex",
+        "table", "grid", "contents", "list-item", "hidden", "flow-root",
+    ],
+    &["aspect"],
+    &["size"],
+    &["h"],
+    &["max-h"],
+    &["min-h"],
+    &["w"],
+    &["min-w"],
+    &["max-w"],
+    &["shrink"],
+    &["grow"],
+    &["basis"],
+    &["caption"],
+    &["border-collapse", "border-separate"],
+    &["border-spacing"],
+    &["origin"],
+    &["translate"],
+    &["rotate"],
+    &["skew"],
+    &["scale"],
+    &["transform"],
+    &["animate"],
+    &["cursor"],
+    &["touch"],
+    &["select"],
+    &["resize"],
+    &["snap"],
+    &["scroll"],
+    &["list"],
+    &["appearance"],
+    &["columns"],
+    &["break-before", "break-inside", "break-after"],
+    &["auto-cols"],
+    &["grid-flow"],
+    &["auto-rows"],
+    &["grid-cols"],
+    &["grid-rows"],
+    &["flex-row", "flex-col", "flex-wrap", "flex-nowrap"],
+    &["place-content"],
+    &["place-items"],
+    &["content"],
+    &["items"],
+    &["justify"],
+    &["justify-items"],
+    &["gap"],
+    &["space"],
+    &["divide"],
+    &["place-self"],
+    &["self"],
+    &["justify-self"],
+    &["overflow"],
+    &["overscroll"],
+    &["truncate", "text-ellipsis", "text-clip"],
+    &["whitespace"],
+    &["break"],
+    &["rounded"],
+    &["border"],
+    &["bg"],
+    &["from", "via", "to"],
+    &["decoration-slice", "decoration-clone"],
+    &["fill"],
+    &["stroke"],
+    &["object"],
+    &["p", "px", "py", "pt", "pr", "pb", "pl", "ps", "pe"],
+    &["indent"],
+    &["align"],
+    &["font"],
+    &["text"],
+    &["uppercase", "lowercase", "capitalize", "normal-case"],
+    &["italic", "not-italic"],
+    &["leading"],
+    &["tracking"],
+    &["underline", "overline", "line-through", "no-underline"],
+    &["decoration"],
+    &["underline-offset"],
+    &["antialiased", "subpixel-antialiased"],
+    &["placeholder"],
+    &["caret"],
+    &["accent"],
+    &["opacity"],
+    &["bg-blend"],
+    &["mix-blend"],
+    &["shadow"],
+    &["outline"],
+    &["outline-offset"],
+    &["ring"],
+    &["ring-offset"],
+    &["blur", "brightness", "contrast", "drop-shadow", "grayscale", "hue-rotate", "invert"],
+    &["saturate", "sepia", "filter"],
+    &["backdrop"],
+    &["transition"],
+    &["delay"],
+    &["duration"],
+    &["ease"],
+    &["will-change"],
+];
+
+/// Position of a utility (variants already stripped) in Tailwind's property order,
+/// and whether it sets its family's color.
+///
+/// Colors sort after the rest of their family, the way `boxShadowColor` follows
+/// `boxShadow`, so `shadow-red-500` still colors a `shadow-lg`. Unknown utilities
+/// come last.
+pub(crate) fn utility_order(utility: &str) -> (usize, bool) {
+    let utility = utility.trim_start_matches('!').trim_start_matches('-');
+
+    let mut best: Option<(usize, &str)> = None;
+    for (rank, family) in UTILITY_ORDER.iter().enumerate() {
+        for name in family.iter().copied() {
+            let matches = utility
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'));
+            if matches && best.map_or(true, |(_, longest)| name.len() > longest.len()) {
+                best = Some((rank, name));
+            }
+        }
+    }
+
+    match best {
+        Some((rank, name)) => {
+            let value = utility[name.len()..].trim_start_matches('-');
+            (rank, !value.is_empty() && is_color_value(value))
+        }
+        None => (UTILITY_ORDER.len(), false),
+    }
+}
+
+/// Leading dash-separated segment of every core Tailwind utility (`bg` for
+/// `bg-blue-500`, `inline` for `inline-flex`). Used to recognise tokens that
+/// cannot possibly be utilities.
+const UTILITY_ROOTS: &[&str] = &[
+    "accent", "align", "animate", "antialiased", "appearance", "aspect", "auto", "backdrop",
+    "basis", "bg", "block", "blur", "border", "bottom", "box", "break", "brightness",
+    "capitalize", "caption", "caret", "clear", "col", "collapse", "columns", "container",
+    "content", "contents", "contrast", "cursor", "decoration", "delay", "diagonal", "divide",
+    "drop", "duration", "ease", "end", "fill", "filter", "fixed", "flex", "float", "flow",
+    "font", "forced", "from", "gap", "grayscale", "grid", "group", "grow", "h", "hidden",
+    "hue", "hyphens", "indent", "inline", "inset", "invert", "invisible", "isolate",
+    "isolation", "italic", "items", "justify", "leading", "left", "line", "lining", "list",
+    "lowercase", "m", "max", "mb", "me", "min", "mix", "ml", "mr", "ms", "mt", "mx", "my",
+    "no", "normal", "not", "object", "oldstyle", "opacity", "order", "ordinal", "origin",
+    "outline", "overflow", "overline", "overscroll", "p", "pb", "pe", "peer", "pl",
+    "place", "placeholder", "pointer", "pr", "proportional", "ps", "pt", "px", "py",
+    "relative", "resize", "right", "ring", "rotate", "rounded", "row", "saturate", "scale",
+    "scroll", "select", "self", "sepia", "shadow", "shrink", "size", "skew", "slashed",
+    "snap", "space", "sr", "stacked", "start", "static", "sticky", "stroke", "subpixel",
+    "table", "tabular", "text", "to", "top", "touch", "tracking", "transform",
+    "transition", "translate", "truncate", "underline", "uppercase", "via", "visible", "w",
+    "whitespace", "will", "z", "absolute",
+];
+
+/// Check whether a class token is obviously a custom (non-Tailwind) class.
+///
+/// This is deliberately conservative: tokens with variants, arbitrary values,
+/// important/negative modifiers or a known utility root are never considered
+/// custom, so skipping `trace()` for custom tokens cannot change the output.
+pub fn is_obviously_custom(class: &str) -> bool {
+    if class.is_empty() || class.starts_with('-') || class.contains([':', '[', '!', '/']) {
+        return false;
+    }
+
+    let root = class.split('-').next().unwrap_or(class);
+    !UTILITY_ROOTS.contains(&root)
+}
+
+/// Split a class into its variant prefixes and the base utility.
+///
+/// Colons inside arbitrary values or arbitrary variants (`[&:hover]:flex`,
+/// `bg-[url(a:b)]`) do not split.
+pub fn split_variants(class: &str) -> (Vec<&str>, &str) {
+    let mut variants = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (idx, ch) in class.char_indices() {
+        match ch {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ':' if depth == 0 => {
+                variants.push(&class[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    (variants, &class[start..])
+}
+
+/// Determine the kind of a single variant prefix (without the trailing `:`)
+pub fn classify_variant(variant: &str) -> VariantKind {
+    if RESPONSIVE_VARIANTS.contains(&variant)
+        || variant.starts_with("max-")
+        || variant.starts_with("min-")
+    {
+        VariantKind::Responsive
+    } else if variant == "dark" {
+        VariantKind::Dark
+    } else if variant.starts_with("group") {
+        VariantKind::Group
+    } else if variant.starts_with("peer") {
+        VariantKind::Peer
+    } else if STATE_VARIANTS.contains(&variant) {
+        VariantKind::State
+    } else {
+        VariantKind::Other
+    }
+}
+
+/// Classify a class by its utility prefix and variant stack
+pub fn classify_class(class: &str) -> ClassClassification {
+    let (variants, base) = split_variants(class);
+    ClassClassification {
+        category: classify_utility(base),
+        variants: variants.into_iter().map(classify_variant).collect(),
+    }
+}
+
+/// Determine the category of a utility with its variants already stripped
+fn classify_utility(utility: &str) -> ClassCategory {
+    // Important and negative modifiers don't change the category
+    let utility = utility.trim_start_matches('!').trim_start_matches('-');
+
+    for (prefix, fallback) in COLOR_PREFIXES {
+        if let Some(value) = utility.strip_prefix(prefix) {
+            return if is_color_value(value) { ClassCategory::Color } else { *fallback };
+        }
+    }
+
+    let matches = |prefixes: &[&str]| prefixes.iter().any(|p| utility.starts_with(p));
+
+    if matches(SPACING_PREFIXES) {
+        ClassCategory::Spacing
+    } else if matches(TYPOGRAPHY_PREFIXES) {
+        ClassCategory::Typography
+    } else if matches(BORDER_PREFIXES) {
+        ClassCategory::Border
+    } else if matches(EFFECT_PREFIXES) {
+        ClassCategory::Effect
+    } else if matches(LAYOUT_PREFIXES) {
+        ClassCategory::Layout
+    } else {
+        ClassCategory::Other
+    }
+}
+
+/// Check whether a utility value (the part after e.g. `bg-`) denotes a color
+fn is_color_value(value: &str) -> bool {
+    // Drop any opacity modifier (`blue-500/50`)
+    let value = value.split('/').next().unwrap_or(value);
+
+    if let Some(arbitrary) = value.strip_prefix('[') {
+        return arbitrary.starts_with('#')
+            || arbitrary.starts_with("rgb")
+            || arbitrary.starts_with("hsl")
+            || arbitrary.starts_with("color:");
+    }
+
+    let name = value.split('-').next().unwrap_or(value);
+    COLOR_NAMES.contains(&name)
+}
+
+/// Check whether a class needs escaping to be used as a CSS class selector.
+///
+/// Identifiers may contain ASCII letters, digits, `-`, `_` and any non-ASCII
+/// character, but must not start with a digit (or `-` followed by a digit).
+/// Anything else, such as the `/` in `w-1/2` or the `:` of a variant, has to be
+/// escaped (`.w-1\/2`).
+pub fn requires_css_escape(class: &str) -> bool {
+    let mut chars = class.chars();
+    let starts_badly = match (chars.next(), chars.next()) {
+        (None, _) => return false,
+        (Some(first), _) if first.is_ascii_digit() => true,
+        (Some('-'), None) => true,
+        (Some('-'), Some(second)) => second.is_ascii_digit(),
+        _ => false,
+    };
+
+    starts_badly
+        || class
+            .chars()
+            .any(|c| c.is_ascii() && !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+/// Check whether a class uses arbitrary-value syntax: an arbitrary value
+/// (`w-[37px]`), an arbitrary property (`[mask-type:luminance]`) or an arbitrary
+/// variant (`[&>*]:p-4`).
+pub fn uses_arbitrary_value(class: &str) -> bool {
+    class
+        .find('[')
+        .and_then(|open| class[open + 1..].find(']'))
+        .is_some_and(|len| len > 0)
+}
+
+/// Describe what's wrong with a class's variant stack, if anything: a repeated
+/// variant (`hover:hover:bg-red-500`) or stacked breakpoints (`md:lg:flex`, where
+/// only the wider one has any effect).
+pub fn redundant_variants(class: &str) -> Option<String> {
+    let (variants, _) = split_variants(class);
+
+    for (index, variant) in variants.iter().enumerate() {
+        if variants[..index].contains(variant) {
+            return Some(format!("`{}:` is repeated", variant));
+        }
+    }
+
+    let breakpoints: Vec<&str> = variants
+        .iter()
+        .copied()
+        .filter(|variant| RESPONSIVE_VARIANTS.contains(variant))
+        .collect();
+    if breakpoints.len() > 1 {
+        return Some(format!("breakpoints `{}:` are stacked", breakpoints.join(":`, `")));
+    }
+
+    None
+}
+
+/// Characters that don't occur in class tokens outside arbitrary values
+const NON_CLASS_CHARS: &[char] = &['"', '\'', '`', '<', '>', '{', '}', ';', '=', '?', ','];
+
+/// Longest token accepted as a class; anything longer is data, not markup
+pub const MAX_CLASS_LENGTH: usize = 256;
+
+/// Why [`validate_class`] rejected a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassRejection {
+    /// The token is empty
+    Empty,
+    /// The token is longer than [`MAX_CLASS_LENGTH`] bytes
+    TooLong,
+    /// The token is a URL (`https://...`)
+    Url,
+    /// The token starts like a path, selector or fragment (`/`, `.`, `#`)
+    LeadingChar(char),
+    /// The token ends like prose (`.`, `,`, `?`, `:`)
+    TrailingChar(char),
+    /// The token contains a character classes never have outside arbitrary values
+    ForbiddenChar(char),
+}
+
+impl std::fmt::Display for ClassRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassRejection::Empty => write!(f, "empty class"),
+            ClassRejection::TooLong => write!(f, "longer than {} bytes", MAX_CLASS_LENGTH),
+            ClassRejection::Url => write!(f, "looks like a URL"),
+            ClassRejection::LeadingChar(c) => write!(f, "starts with `{}`", c),
+            ClassRejection::TrailingChar(c) => write!(f, "ends with `{}`", c),
+            ClassRejection::ForbiddenChar(c) => write!(f, "contains `{}` outside brackets", c),
+        }
+    }
+}
+
+/// Check a single token for things classes never contain, explaining rejections.
+///
+/// ```
+/// use tailwind_extractor::{validate_class, ClassRejection};
+///
+/// assert_eq!(validate_class("hover:bg-[#fff]"), Ok(()));
+/// assert_eq!(validate_class("a=b"), Err(ClassRejection::ForbiddenChar('=')));
+/// ```
+pub fn validate_class(class: &str) -> Result<(), ClassRejection> {
+    if class.is_empty() {
+        return Err(ClassRejection::Empty);
+    }
+    if class.len() > MAX_CLASS_LENGTH {
+        return Err(ClassRejection::TooLong);
+    }
+    if class.contains("://") {
+        return Err(ClassRejection::Url);
+    }
+    if let Some(c) = class.chars().next().filter(|c| ['/', '.', '#'].contains(c)) {
+        return Err(ClassRejection::LeadingChar(c));
+    }
+    if let Some(c) = class.chars().last().filter(|c| ['.', ',', '?', ':'].contains(c)) {
+        return Err(ClassRejection::TrailingChar(c));
+    }
+
+    let mut depth = 0usize;
+    for c in class.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && NON_CLASS_CHARS.contains(&c) => return Err(ClassRejection::ForbiddenChar(c)),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a single whitespace-separated token for things classes never contain
+fn is_class_token(token: &str) -> bool {
+    validate_class(token).is_ok()
+}
+
+/// Split a class list on whitespace outside brackets and parentheses, so
+/// `grid-cols-[repeat(3, minmax(0, 1fr))]` stays one token
+pub(crate) fn split_class_tokens(value: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+
+    for (idx, c) in value.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    tokens.push(&value[begin..idx]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(idx);
+    }
+    if let Some(begin) = start {
+        tokens.push(&value[begin..]);
+    }
+
+    tokens
+}
+
+/// Whether a string looks like a list of classes.
+///
+/// URLs, paths and prose are rejected. In a class context (e.g. a `className`
+/// value) any well-formed tokens qualify; elsewhere at least one token must look
+/// like a Tailwind utility, since arbitrary words would match too.
+///
+/// ```
+/// use tailwind_extractor::is_class_like;
+///
+/// assert!(is_class_like("flex p-4 hover:bg-blue-500", false));
+/// assert!(is_class_like("btn card", true));
+/// assert!(!is_class_like("btn card", false));
+/// assert!(!is_class_like("https://example.com/p-4", true));
+/// assert!(!is_class_like("Hello, world.", true));
+/// ```
+pub fn is_class_like(value: &str, in_class_context: bool) -> bool {
+    let mut has_utility = false;
+    let mut has_tokens = false;
+
+    for token in split_class_tokens(value) {
+        if !is_class_token(token) {
+            return false;
+        }
+        has_tokens = true;
+        has_utility |= !is_obviously_custom(token);
+    }
+
+    has_tokens && (in_class_context || has_utility)
+}
+
+/// Count classes per variant chain (`md`, `hover`, `md:hover`), with `base` for
+/// classes without variants. Keys appear in first-seen order.
+pub fn variant_breakdown<S: AsRef<str>>(classes: &[S]) -> IndexMap<String, usize> {
+    let mut breakdown = IndexMap::new();
+    for class in classes {
+        let (variants, _) = split_variants(class.as_ref());
+        let key = if variants.is_empty() {
+            "base".to_string()
+        } else {
+            variants.join(":")
+        };
+        *breakdown.entry(key).or_insert(0) += 1;
+    }
+    breakdown
+}
+
+/// Collapse classes that differ only by case, such as `Bg-Blue-500` and `bg-blue-500`.
+///
+/// The lowercase spelling is kept (or the first one seen if none is lowercase).
+/// Classes with arbitrary values are left alone since `bg-[#FFF]` and `bg-[#fff]`
+/// are both legitimate. Returns the kept classes in their original order and the
+/// `(kept, dropped)` pairs.
+pub fn dedup_case_insensitive(classes: &[String]) -> (Vec<String>, Vec<(String, String)>) {
+    let mut kept_by_key: IndexMap<String, String> = IndexMap::new();
+    for class in classes {
+        if class.contains('[') {
+            continue;
+        }
+        let key = class.to_lowercase();
+        match kept_by_key.get(&key) {
+            Some(kept) if *kept == key => {}
+            Some(_) if *class == key => {
+                kept_by_key.insert(key, class.clone());
+            }
+            Some(_) => {}
+            None => {
+                kept_by_key.insert(key, class.clone());
+            }
+        }
+    }
+
+    let mut kept = Vec::with_capacity(classes.len());
+    let mut dropped = Vec::new();
+    for class in classes {
+        match kept_by_key.get(&class.to_lowercase()) {
+            Some(winner) if !class.contains('[') && winner != class => {
+                dropped.push((winner.clone(), class.clone()));
+            }
+            _ => kept.push(class.clone()),
+        }
+    }
+
+    (kept, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_color_and_responsive_variant() {
+        let color = classify_class("bg-blue-500");
+        assert_eq!(color.category, ClassCategory::Color);
+        assert!(color.variants.is_empty());
+
+        let responsive = classify_class("md:flex");
+        assert_eq!(responsive.category, ClassCategory::Layout);
+        assert_eq!(responsive.variants, vec![VariantKind::Responsive]);
+    }
+
+    #[test]
+    fn test_classify_group_and_peer_modifiers() {
+        let group = classify_class("group-hover:text-white");
+        assert_eq!(group.category, ClassCategory::Color);
+        assert_eq!(group.variants, vec![VariantKind::Group]);
+
+        let peer = classify_class("md:peer-checked:p-4");
+        assert_eq!(peer.category, ClassCategory::Spacing);
+        assert_eq!(peer.variants, vec![VariantKind::Responsive, VariantKind::Peer]);
+
+        // `text-lg` shares the `text-` prefix but is not a color
+        assert_eq!(classify_class("text-lg").category, ClassCategory::Typography);
+    }
+
+    #[test]
+    fn test_split_variants_ignores_colons_in_brackets() {
+        assert_eq!(split_variants("hover:bg-[url(a:b)]"), (vec!["hover"], "bg-[url(a:b)]"));
+        assert_eq!(split_variants("[&:hover]:flex"), (vec!["[&:hover]"], "flex"));
+    }
+
+    #[test]
+    fn test_requires_css_escape() {
+        assert!(requires_css_escape("w-1/2"));
+        assert!(requires_css_escape("gap-[0.25rem]"));
+        assert!(requires_css_escape("md:flex"));
+        assert!(requires_css_escape("2xl"));
+
+        assert!(!requires_css_escape("p-4"));
+        assert!(!requires_css_escape("-mt-2"));
+        assert!(!requires_css_escape("custom_class"));
+    }
+
+    #[test]
+    fn test_validate_class_reasons() {
+        assert_eq!(validate_class("md:w-[calc(100%-1rem)]"), Ok(()));
+        assert_eq!(validate_class(""), Err(ClassRejection::Empty));
+        assert_eq!(validate_class(&"x".repeat(MAX_CLASS_LENGTH + 1)), Err(ClassRejection::TooLong));
+        assert_eq!(validate_class("https://example.com"), Err(ClassRejection::Url));
+        assert_eq!(validate_class("/images/logo.png"), Err(ClassRejection::LeadingChar('/')));
+        assert_eq!(validate_class("#main"), Err(ClassRejection::LeadingChar('#')));
+        assert_eq!(validate_class("Hello,"), Err(ClassRejection::TrailingChar(',')));
+        assert_eq!(validate_class("a{b"), Err(ClassRejection::ForbiddenChar('{')));
+        assert_eq!(validate_class("content-['a;b']"), Ok(()));
+        assert_eq!(ClassRejection::ForbiddenChar('=').to_string(), "contains `=` outside brackets");
+    }
+
+    #[test]
+    fn test_nested_arbitrary_grid_values() {
+        assert!(is_class_like("grid-cols-[repeat(3,minmax(0,1fr))]", false));
+        assert!(is_class_like("grid grid-cols-[repeat(3, minmax(0, 1fr))] gap-4", false));
+        assert_eq!(
+            split_class_tokens("grid grid-cols-[repeat(3, minmax(0, 1fr))]"),
+            vec!["grid", "grid-cols-[repeat(3, minmax(0, 1fr))]"]
+        );
+        assert!(!is_class_like("a, b", true));
+    }
+
+    #[test]
+    fn test_uses_arbitrary_value() {
+        assert!(uses_arbitrary_value("w-[37px]"));
+        assert!(uses_arbitrary_value("[mask-type:luminance]"));
+        assert!(uses_arbitrary_value("[&>*]:p-4"));
+        assert!(uses_arbitrary_value("md:bg-[#1da1f2]"));
+
+        assert!(!uses_arbitrary_value("w-1/2"));
+        assert!(!uses_arbitrary_value("p-4"));
+        assert!(!uses_arbitrary_value("w-[]"));
+    }
+
+    #[test]
+    fn test_redundant_variants() {
+        assert_eq!(redundant_variants("hover:hover:bg-red-500").as_deref(), Some("`hover:` is repeated"));
+        assert_eq!(redundant_variants("md:hover:md:flex").as_deref(), Some("`md:` is repeated"));
+        assert_eq!(redundant_variants("md:lg:flex").as_deref(), Some("breakpoints `md:`, `lg:` are stacked"));
+
+        assert_eq!(redundant_variants("md:hover:bg-red-500"), None);
+        assert_eq!(redundant_variants("md:max-lg:flex"), None);
+        assert_eq!(redundant_variants("[&:hover]:hover:flex"), None);
+        assert_eq!(redundant_variants("p-4"), None);
+    }
+
+    #[test]
+    fn test_utility_order() {
+        let (shadow, shadow_is_color) = utility_order("shadow-lg");
+        let (shadow_color, shadow_color_is_color) = utility_order("shadow-red-500");
+        assert_eq!(shadow, shadow_color);
+        assert!(!shadow_is_color && shadow_color_is_color);
+
+        assert!(utility_order("flex").0 < utility_order("p-4").0);
+        assert!(utility_order("p-4").0 < utility_order("text-lg").0);
+        assert_eq!(utility_order("-mx-2").0, utility_order("m-4").0);
+        assert!(utility_order("max-w-md").0 > utility_order("w-4").0);
+        assert_eq!(utility_order("btn-primary").0, UTILITY_ORDER.len());
+    }
+
+    #[test]
+    fn test_is_obviously_custom() {
+        assert!(is_obviously_custom("btn-primary"));
+        assert!(is_obviously_custom("is-open"));
+        assert!(is_obviously_custom("card"));
+
+        assert!(!is_obviously_custom("bg-blue-500"));
+        assert!(!is_obviously_custom("inline-flex"));
+        assert!(!is_obviously_custom("md:card"));
+        assert!(!is_obviously_custom("-mt-2"));
+        assert!(!is_obviously_custom("custom-[10px]"));
+    }
+
+    #[test]
+    fn test_variant_breakdown() {
+        let breakdown = variant_breakdown(&["flex", "md:flex", "hover:bg-red-500", "p-4", "md:p-2", "md:hover:underline"]);
+
+        assert_eq!(breakdown["base"], 2);
+        assert_eq!(breakdown["md"], 2);
+        assert_eq!(breakdown["hover"], 1);
+        assert_eq!(breakdown["md:hover"], 1);
+        assert_eq!(breakdown.keys().collect::<Vec<_>>(), vec!["base", "md", "hover", "md:hover"]);
+    }
+
+    #[test]
+    fn test_dedup_case_insensitive() {
+        let classes: Vec<String> = ["Flex", "p-4", "flex", "bg-[#FFF]", "bg-[#fff]"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        let (kept, dropped) = dedup_case_insensitive(&classes);
+
+        assert_eq!(kept, vec!["p-4", "flex", "bg-[#FFF]", "bg-[#fff]"]);
+        assert_eq!(dropped, vec![("flex".to_string(), "Flex".to_string())]);
+    }
+
+    #[test]
+    fn test_utility_roots_cover_traceable_classes() {
+        let suffixes = ["", "-0", "-4", "-x-4", "-auto", "-none", "-full", "-lg", "-blue-500"];
+        let extras = [
+            "tabular-nums", "ordinal", "slashed-zero", "diagonal-fractions", "hyphens-auto",
+            "forced-color-adjust-auto", "ring-offset-2", "bg-opacity-50", "table-auto", "flow-root",
+        ];
+        let candidates = UTILITY_ORDER
+            .iter()
+            .flat_map(|family| family.iter())
+            .flat_map(|name| suffixes.iter().map(move |suffix| format!("{}{}", name, suffix)))
+            .chain(extras.iter().map(|class| class.to_string()));
+
+        let mut builder = tailwind_rs::TailwindBuilder::default();
+        let mut traced = 0;
+        for class in candidates {
+            let Ok(result) = builder.trace(&class, true) else {
+                continue;
+            };
+            if result != class {
+                traced += 1;
+                assert!(!is_obviously_custom(&class), "`{}` traces but its root is missing from UTILITY_ROOTS", class);
+            }
+        }
+        assert!(traced > 50, "only {} candidates traced", traced);
+    }
+
+    #[test]
+    fn test_is_class_like() {
+        assert!(is_class_like("w-1/2 bg-[url('/img.png')] !p-4", false));
+        assert!(is_class_like("md:flex", false));
+
+        assert!(!is_class_like("", true));
+        assert!(!is_class_like("/assets/logo.svg", true));
+        assert!(!is_class_like("Are you sure?", true));
+        assert!(!is_class_like("a { color: red; }", true));
+    }
+}
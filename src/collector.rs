@@ -0,0 +1,79 @@
+//! Shared class bookkeeping for the non-JavaScript front ends
+//!
+//! JSON and component-template scanners find class strings without going through
+//! the AST transformer. `ClassCollector` gives them the same trace-and-record
+//! behaviour and lets them fold in metadata from embedded script blocks.
+
+use indexmap::{IndexMap, IndexSet};
+use tailwind_rs::TailwindBuilder;
+
+use crate::ast_transformer::{parse_tailwind_classes, TransformConfig, TransformMetadata};
+
+/// Traces class strings and records the classes they contain
+pub(crate) struct ClassCollector {
+    /// Tailwind builder for class processing
+    tailwind: TailwindBuilder,
+    /// Configuration settings
+    config: TransformConfig,
+    /// Collected classes (deduplicated)
+    classes: IndexSet<String>,
+    /// Count of all classes before deduplication
+    total_count: usize,
+    /// Warnings from nested transforms
+    warnings: Vec<String>,
+}
+
+impl ClassCollector {
+    pub(crate) fn new(config: TransformConfig) -> Self {
+        Self {
+            tailwind: TailwindBuilder::default(),
+            config,
+            classes: IndexSet::new(),
+            total_count: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Configuration the collector was created with
+    pub(crate) fn config(&self) -> &TransformConfig {
+        &self.config
+    }
+
+    /// Process a class string and record its classes
+    pub(crate) fn process_string(&mut self, value: &str) -> String {
+        let processed = match self.tailwind.trace(value, self.config.obfuscate) {
+            Ok(result) => result.into_owned(),
+            Err(_) => value.to_string(), // Fallback to original on error
+        };
+
+        for class in parse_tailwind_classes(value) {
+            self.classes.insert(class);
+            self.total_count += 1;
+        }
+
+        processed
+    }
+
+    /// Record a class without tracing it
+    pub(crate) fn record(&mut self, class: &str) {
+        self.classes.insert(class.to_string());
+        self.total_count += 1;
+    }
+
+    /// Merge metadata produced by a nested transform (e.g. a `<script>` block)
+    pub(crate) fn merge(&mut self, metadata: TransformMetadata) {
+        self.classes.extend(metadata.classes);
+        self.total_count += metadata.original_count;
+        self.warnings.extend(metadata.warnings);
+    }
+
+    /// Finish collection
+    pub(crate) fn into_metadata(self) -> TransformMetadata {
+        TransformMetadata {
+            classes: self.classes.into_iter().collect(),
+            original_count: self.total_count,
+            warnings: self.warnings,
+            locations: IndexMap::new(),
+        }
+    }
+}
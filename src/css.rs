@@ -0,0 +1,806 @@
+//! Post-processing for generated CSS
+//!
+//! tailwind-rs returns the bundle as a single string. The helpers in this module
+//! reshape that string (indentation, line breaks) without changing its meaning,
+//! or drop rules for classes that are no longer used.
+
+use std::collections::HashSet;
+
+use crate::class_utils::{split_class_tokens, split_variants, utility_order, UTILITY_ORDER};
+
+/// Layout of the generated stylesheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CssStyle {
+    /// One rule per line: `.p-4 { padding: 1rem; }`
+    Compact,
+    /// Every declaration on its own indented line
+    Expanded,
+}
+
+/// Order of the rules in the generated stylesheet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CssOrder {
+    /// Keep the order tailwind-rs bundles the rules in
+    #[default]
+    Bundle,
+    /// Base rules first, then utilities in Tailwind's property order, then media queries
+    Canonical,
+}
+
+/// A node of the parsed stylesheet
+#[derive(Debug, Clone, PartialEq)]
+enum CssNode {
+    /// A rule or at-rule with a block: `prelude { children }`
+    Block { prelude: String, children: Vec<CssNode> },
+    /// A declaration or block-less at-rule, without the trailing `;`
+    Statement(String),
+    /// A comment, including its delimiters
+    Comment(String),
+}
+
+/// Minimal CSS parser that only tracks structure: blocks, statements and comments
+struct CssParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> CssParser<'a> {
+    fn new(css: &'a str) -> Self {
+        Self {
+            chars: css.chars().peekable(),
+        }
+    }
+
+    /// Parse nodes until the end of input or the `}` closing the current block
+    fn parse_nodes(&mut self) -> Vec<CssNode> {
+        let mut nodes = Vec::new();
+        let mut buffer = String::new();
+        let mut paren_depth = 0usize;
+
+        while let Some(ch) = self.chars.next() {
+            match ch {
+                // Escaped quotes and braces (`.content-\[\'x\'\]`, `.a\{b`) are part of a selector
+                '\\' => {
+                    buffer.push(ch);
+                    buffer.extend(self.chars.next());
+                }
+                '"' | '\'' => self.copy_string(ch, &mut buffer),
+                '/' if self.chars.peek() == Some(&'*') => {
+                    let comment = self.read_comment();
+                    if buffer.trim().is_empty() {
+                        nodes.push(CssNode::Comment(comment));
+                    } else {
+                        buffer.push_str(&comment);
+                    }
+                }
+                '(' => {
+                    paren_depth += 1;
+                    buffer.push(ch);
+                }
+                ')' => {
+                    paren_depth = paren_depth.saturating_sub(1);
+                    buffer.push(ch);
+                }
+                ';' if paren_depth == 0 => {
+                    push_statement(&mut nodes, &mut buffer);
+                }
+                '{' => {
+                    let prelude = buffer.trim().to_string();
+                    buffer.clear();
+                    let children = self.parse_nodes();
+                    nodes.push(CssNode::Block { prelude, children });
+                }
+                '}' => {
+                    push_statement(&mut nodes, &mut buffer);
+                    return nodes;
+                }
+                c if c.is_whitespace() => {
+                    // Collapse whitespace runs outside strings
+                    if !buffer.is_empty() && !buffer.ends_with(' ') {
+                        buffer.push(' ');
+                    }
+                }
+                c => buffer.push(c),
+            }
+        }
+
+        push_statement(&mut nodes, &mut buffer);
+        nodes
+    }
+
+    /// Copy a quoted string verbatim, including escapes
+    fn copy_string(&mut self, quote: char, buffer: &mut String) {
+        buffer.push(quote);
+        while let Some(ch) = self.chars.next() {
+            buffer.push(ch);
+            if ch == '\\' {
+                if let Some(escaped) = self.chars.next() {
+                    buffer.push(escaped);
+                }
+            } else if ch == quote {
+                break;
+            }
+        }
+    }
+
+    /// Read a comment whose leading `/` has already been consumed
+    fn read_comment(&mut self) -> String {
+        self.chars.next(); // the opening `*`
+        let mut comment = String::from("/*");
+        let mut prev = '\0';
+        for ch in self.chars.by_ref() {
+            comment.push(ch);
+            if prev == '*' && ch == '/' {
+                break;
+            }
+            prev = ch;
+        }
+        comment
+    }
+}
+
+/// Flush the buffer as a statement node if it holds anything
+fn push_statement(nodes: &mut Vec<CssNode>, buffer: &mut String) {
+    let statement = buffer.trim();
+    if !statement.is_empty() {
+        nodes.push(CssNode::Statement(statement.to_string()));
+    }
+    buffer.clear();
+}
+
+/// Print nodes at the given nesting depth
+fn print_nodes(nodes: &[CssNode], style: CssStyle, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    for node in nodes {
+        match node {
+            CssNode::Comment(comment) => {
+                output.push_str(&indent);
+                output.push_str(comment);
+                output.push('\n');
+            }
+            CssNode::Statement(statement) => {
+                output.push_str(&indent);
+                output.push_str(statement);
+                output.push_str(";\n");
+            }
+            CssNode::Block { prelude, children } => {
+                output.push_str(&indent);
+                output.push_str(prelude);
+
+                let is_leaf = children.iter().all(|c| matches!(c, CssNode::Statement(_)));
+                if style == CssStyle::Compact && is_leaf {
+                    output.push_str(" {");
+                    for child in children {
+                        if let CssNode::Statement(statement) = child {
+                            output.push(' ');
+                            output.push_str(statement);
+                            output.push(';');
+                        }
+                    }
+                    output.push_str(" }\n");
+                } else {
+                    output.push_str(" {\n");
+                    print_nodes(children, style, depth + 1, output);
+                    output.push_str(&indent);
+                    output.push_str("}\n");
+                }
+            }
+        }
+    }
+}
+
+/// Declarations of a stylesheet that consists of a single plain class rule.
+///
+/// Returns `None` for multiple rules, nested rules (media queries) and selectors
+/// with pseudo-classes or combinators, none of which can become an inline style.
+#[cfg(feature = "cli")]
+pub(crate) fn single_rule_declarations(css: &str) -> Option<Vec<(String, String)>> {
+    let nodes = CssParser::new(css).parse_nodes();
+    let rules: Vec<&CssNode> = nodes
+        .iter()
+        .filter(|node| !matches!(node, CssNode::Comment(_)))
+        .collect();
+    let [CssNode::Block { prelude, children }] = rules.as_slice() else {
+        return None;
+    };
+
+    let mut escaped = false;
+    for ch in prelude.chars() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ':' | ' ' | '>' | '+' | '~' | ',' | '[' => return None,
+            _ => {}
+        }
+    }
+
+    children
+        .iter()
+        .map(|child| match child {
+            CssNode::Statement(statement) => statement
+                .split_once(':')
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Class names referenced by `.class` selectors in `text`, with CSS escapes
+/// (`.w-1\/2`, `.\32xl`) decoded.
+///
+/// An unescaped digit can't start a class selector, so numbers such as the
+/// `12.5%` keyframe step are not read as classes.
+pub(crate) fn selector_class_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '.' || chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next == '\\' {
+                chars.next();
+                let mut hex = String::new();
+                while hex.len() < 6 && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    hex.extend(chars.next());
+                }
+                if hex.is_empty() {
+                    name.extend(chars.next());
+                } else {
+                    // A single space terminates a hex escape
+                    if chars.peek() == Some(&' ') {
+                        chars.next();
+                    }
+                    name.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                }
+            } else if next.is_alphanumeric() || next == '-' || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if !name.is_empty() {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+/// Escape a class name for use in a `.class` selector, like the browser's
+/// `CSS.escape`: `w-1/2` becomes `w-1\/2` and `2xl:p-4` becomes `\32 xl\:p-4`
+pub fn escape_class(class: &str) -> String {
+    let mut escaped = String::with_capacity(class.len() * 2);
+
+    for (idx, c) in class.chars().enumerate() {
+        let leading_digit = c.is_ascii_digit()
+            && (idx == 0 || (idx == 1 && class.starts_with('-')));
+        if leading_digit {
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else if c.is_ascii() && !(c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            escaped.push('\\');
+            escaped.push(c);
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+/// Classes without a correctly escaped `.class` selector in `css`.
+///
+/// A selector emitted unescaped (`.w-1/2`) doesn't decode back to its class, so
+/// it is reported here, as are classes that generated no CSS at all.
+pub fn unmatched_class_selectors(css: &str, classes: &[String]) -> Vec<String> {
+    let names: HashSet<String> = selector_class_names(css).into_iter().collect();
+    classes
+        .iter()
+        .filter(|class| !names.contains(*class))
+        .cloned()
+        .collect()
+}
+
+/// Utilities listed in `@apply` directives, e.g. `px-4` and `py-2` from `@apply px-4 py-2;`
+pub fn apply_directive_classes(css: &str) -> Vec<String> {
+    let mut classes = Vec::new();
+
+    for (index, _) in css.match_indices("@apply") {
+        let rest = &css[index + "@apply".len()..];
+        let end = rest.find([';', '}', '\n']).unwrap_or(rest.len());
+        classes.extend(
+            rest[..end]
+                .split_whitespace()
+                .filter(|class| *class != "!important")
+                .map(str::to_string),
+        );
+    }
+
+    classes
+}
+
+/// Replace `@tailwind base;` and `@tailwind utilities;` in an entry stylesheet with the
+/// generated preflight and utilities. Other layers (`components`, `variants`) have no
+/// tailwind-rs counterpart and are removed.
+pub fn substitute_tailwind_directives(template: &str, base: &str, utilities: &str) -> String {
+    let mut output = String::with_capacity(template.len() + base.len() + utilities.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find("@tailwind") {
+        output.push_str(&rest[..index]);
+        let after = &rest[index + "@tailwind".len()..];
+        let end = after.find(';').map_or(after.len(), |end| end + 1);
+        match after[..end].trim_end_matches(';').trim() {
+            "base" => output.push_str(base.trim_end_matches('\n')),
+            "utilities" => output.push_str(utilities.trim_end_matches('\n')),
+            _ => {}
+        }
+        rest = &after[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Drop selectors whose classes are not all in `used`, and rules left without selectors
+fn purge_nodes(nodes: Vec<CssNode>, used: &HashSet<String>) -> Vec<CssNode> {
+    nodes
+        .into_iter()
+        .filter_map(|node| match node {
+            // Keyframe steps (`from`, `12.5%`) are not selectors
+            CssNode::Block { prelude, children } if prelude.starts_with('@') && prelude.contains("keyframes") => {
+                Some(CssNode::Block { prelude, children })
+            }
+            CssNode::Block { prelude, children } if prelude.starts_with('@') => {
+                let had_blocks = children.iter().any(|c| matches!(c, CssNode::Block { .. }));
+                let children = purge_nodes(children, used);
+                // Drop at-rules like @media once all their rules are gone
+                let emptied = had_blocks && !children.iter().any(|c| matches!(c, CssNode::Block { .. }));
+                (!emptied).then_some(CssNode::Block { prelude, children })
+            }
+            CssNode::Block { prelude, children } => {
+                let selectors: Vec<&str> = prelude
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|selector| {
+                        selector_class_names(selector)
+                            .iter()
+                            .all(|class| used.contains(class))
+                    })
+                    .collect();
+                (!selectors.is_empty()).then(|| CssNode::Block {
+                    prelude: selectors.join(", "),
+                    children,
+                })
+            }
+            other => Some(other),
+        })
+        .collect()
+}
+
+/// Sort key of a node: base rules, then utilities (rules without variants first, each
+/// group in Tailwind's property order), then media queries.
+///
+/// `original` maps a selector's class back to the class it was generated for, which
+/// differs when selectors are obfuscated.
+fn canonical_rank(node: &CssNode, original: &dyn Fn(&str) -> String) -> (usize, usize, usize, bool) {
+    match node {
+        CssNode::Block { prelude, .. } if prelude.starts_with("@media") => (2, 0, 0, false),
+        CssNode::Block { prelude, .. } if !prelude.starts_with('@') => {
+            match selector_class_names(prelude).first() {
+                Some(class) => {
+                    let class = original(class);
+                    let (variants, base) = split_variants(&class);
+                    let (rank, is_color) = utility_order(base);
+                    (1, variants.len(), rank, is_color)
+                }
+                None => (0, 0, 0, false),
+            }
+        }
+        _ => (0, 0, 0, false),
+    }
+}
+
+/// Stable-sort rules (and the rules inside media queries) into canonical order
+fn sort_nodes(nodes: &mut [CssNode], original: &dyn Fn(&str) -> String) {
+    nodes.sort_by_cached_key(|node| canonical_rank(node, original));
+    for node in nodes {
+        if let CssNode::Block { prelude, children } = node {
+            if prelude.starts_with("@media") {
+                sort_nodes(children, original);
+            }
+        }
+    }
+}
+
+/// Reorder the classes of a class list the way the Prettier Tailwind plugin does:
+/// unknown (custom) classes first, then classes without variants, each group in
+/// Tailwind's property order.
+///
+/// The sort is stable, so classes of the same rank keep their relative order. Leading
+/// and trailing whitespace is kept; whitespace between classes becomes single spaces,
+/// while arbitrary values such as `grid-cols-[repeat(3, minmax(0, 1fr))]` stay intact.
+pub fn sort_class_list(value: &str) -> String {
+    let mut classes = split_class_tokens(value);
+    classes.sort_by_cached_key(|class| {
+        let (variants, base) = split_variants(class);
+        match utility_order(base) {
+            (rank, _) if rank == UTILITY_ORDER.len() => (false, 0, 0, false),
+            (rank, is_color) => (true, variants.len(), rank, is_color),
+        }
+    });
+
+    let trimmed = value.trim_start();
+    let leading = &value[..value.len() - trimmed.len()];
+    let trailing = &trimmed[trimmed.trim_end().len()..];
+    format!("{}{}{}", leading, classes.join(" "), trailing)
+}
+
+/// Reorder rules into Tailwind's canonical order, printing the result in expanded style.
+///
+/// Rules without class selectors (resets, `@keyframes`) come first. Utilities follow
+/// in Tailwind's property order (so `.flex` precedes `.bg-blue-500` and `.shadow-lg`
+/// precedes `.shadow-red-500`), with variant rules after base rules. Media queries
+/// come last so responsive variants still override base utilities.
+pub fn sort_css_rules(css: &str) -> String {
+    sort_css_rules_by(css, &|class| class.to_string())
+}
+
+/// [`sort_css_rules`] for obfuscated CSS, ranking each selector by the class
+/// `original` maps it back to
+pub(crate) fn sort_css_rules_by(css: &str, original: &dyn Fn(&str) -> String) -> String {
+    let mut nodes = CssParser::new(css).parse_nodes();
+    sort_nodes(&mut nodes, original);
+    let mut output = String::with_capacity(css.len());
+    print_nodes(&nodes, CssStyle::Expanded, 0, &mut output);
+    output
+}
+
+/// Remove rules for classes missing from `used`, printing the result in expanded style.
+///
+/// Rules without class selectors (element resets, `@keyframes` steps) are kept.
+pub fn purge_css(css: &str, used: &HashSet<String>) -> String {
+    let nodes = purge_nodes(CssParser::new(css).parse_nodes(), used);
+    let mut output = String::with_capacity(css.len());
+    print_nodes(&nodes, CssStyle::Expanded, 0, &mut output);
+    output
+}
+
+/// Collapse whitespace runs outside quoted strings to a single space; escaped
+/// characters are copied as is
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut quote = None;
+    let mut pending_space = false;
+    let mut chars = text.trim().chars();
+
+    while let Some(ch) = chars.next() {
+        match quote {
+            Some(q) => {
+                output.push(ch);
+                if ch == '\\' {
+                    output.extend(chars.next());
+                } else if ch == q {
+                    quote = None;
+                }
+            }
+            None if ch.is_whitespace() => pending_space = true,
+            None => {
+                if pending_space {
+                    output.push(' ');
+                    pending_space = false;
+                }
+                if ch == '"' || ch == '\'' {
+                    quote = Some(ch);
+                }
+                output.push(ch);
+                if ch == '\\' {
+                    output.extend(chars.next());
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Shortest form of a rule prelude: `.a > .b, .c` becomes `.a>.b,.c`.
+///
+/// Quoted attribute values such as `[data-x="a > b"]` are kept as written.
+fn minify_prelude(prelude: &str) -> String {
+    let collapsed = collapse_whitespace(prelude);
+    if prelude.starts_with('@') {
+        return collapsed;
+    }
+
+    let is_combinator = |ch: char| matches!(ch, ',' | '>' | '+' | '~');
+    let mut minified = String::with_capacity(collapsed.len());
+    let mut quote = None;
+    let mut after_combinator = false;
+    let mut escaped = false;
+    for ch in collapsed.chars() {
+        if escaped {
+            escaped = false;
+            after_combinator = false;
+            minified.push(ch);
+            continue;
+        }
+        escaped = ch == '\\';
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == ' ' && after_combinator => continue,
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if is_combinator(ch) && minified.ends_with(' ') => {
+                minified.pop();
+            }
+            None => {}
+        }
+        after_combinator = quote.is_none() && is_combinator(ch);
+        minified.push(ch);
+    }
+    minified
+}
+
+/// Shortest form of a declaration: `padding: 1rem` becomes `padding:1rem`
+fn minify_statement(statement: &str) -> String {
+    match statement.split_once(':') {
+        Some((name, value)) if !statement.starts_with('@') => {
+            format!("{}:{}", name.trim(), collapse_whitespace(value))
+        }
+        _ => collapse_whitespace(statement),
+    }
+}
+
+/// Print nodes without any optional whitespace, keeping only `/*!` comments
+fn print_minified(nodes: &[CssNode], output: &mut String) {
+    let mut after_statement = false;
+
+    for node in nodes {
+        match node {
+            CssNode::Comment(comment) => {
+                if comment.starts_with("/*!") {
+                    output.push_str(comment);
+                }
+            }
+            CssNode::Statement(statement) => {
+                if after_statement {
+                    output.push(';');
+                }
+                output.push_str(&minify_statement(statement));
+                after_statement = true;
+                continue;
+            }
+            CssNode::Block { prelude, children } => {
+                if after_statement {
+                    output.push(';');
+                }
+                output.push_str(&minify_prelude(prelude));
+                output.push('{');
+                print_minified(children, output);
+                output.push('}');
+            }
+        }
+        after_statement = false;
+    }
+}
+
+/// Remove all optional whitespace and comments from a stylesheet.
+///
+/// License comments (`/*! ... */`) are kept, as minifiers conventionally do.
+pub fn minify_css(css: &str) -> String {
+    let nodes = CssParser::new(css).parse_nodes();
+    let mut output = String::with_capacity(css.len());
+    print_minified(&nodes, &mut output);
+    output
+}
+
+/// Reformat a stylesheet in the given style
+pub fn format_css(css: &str, style: CssStyle) -> String {
+    let nodes = CssParser::new(css).parse_nodes();
+    let mut output = String::with_capacity(css.len());
+    print_nodes(&nodes, style, 0, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSS: &str = ".p-4{padding:1rem}\n@media (min-width: 768px) {\n.md\\:flex {display: flex; gap: 0.5rem;}\n}\n.bg-x { background: url(\"a;b.svg\"); }";
+
+    /// Selectors whose escaped quotes and braces must not open a string or a block
+    const ESCAPED: &str = r".content-\[\'x\'\] { content: 'x' }
+.a\{b { color: red }
+.p-4 { padding: 1rem }";
+
+    #[test]
+    fn test_format_keeps_escaped_selectors() {
+        let formatted = format_css(ESCAPED, CssStyle::Expanded);
+
+        assert_eq!(
+            formatted,
+            ".content-\\[\\'x\\'\\] {\n  content: 'x';\n}\n.a\\{b {\n  color: red;\n}\n.p-4 {\n  padding: 1rem;\n}\n"
+        );
+        assert_eq!(
+            minify_css(ESCAPED),
+            ".content-\\[\\'x\\'\\]{content:'x'}.a\\{b{color:red}.p-4{padding:1rem}"
+        );
+    }
+
+    #[test]
+    fn test_purge_keeps_escaped_selectors() {
+        let used: HashSet<String> = ["content-['x']", "p-4"].iter().map(|c| c.to_string()).collect();
+
+        let purged = purge_css(ESCAPED, &used);
+
+        assert!(purged.contains(".content-\\[\\'x\\'\\] {\n  content: 'x';\n}"), "{}", purged);
+        assert!(purged.contains(".p-4 {\n  padding: 1rem;\n}"), "{}", purged);
+        assert!(!purged.contains("color"), "{}", purged);
+    }
+
+    #[test]
+    fn test_sort_keeps_escaped_selectors() {
+        let sorted = sort_css_rules(ESCAPED);
+
+        assert_eq!(sorted.matches(" {\n").count(), 3, "{}", sorted);
+        assert!(sorted.contains(".a\\{b {\n  color: red;\n}"), "{}", sorted);
+        assert!(sorted.contains(".p-4 {\n  padding: 1rem;\n}"), "{}", sorted);
+    }
+
+    #[test]
+    fn test_expanded_puts_declarations_on_separate_lines() {
+        let formatted = format_css(CSS, CssStyle::Expanded);
+
+        assert!(formatted.contains(".p-4 {\n  padding:1rem;\n}\n"), "{}", formatted);
+        assert!(
+            formatted.contains("  .md\\:flex {\n    display: flex;\n    gap: 0.5rem;\n  }\n"),
+            "{}",
+            formatted
+        );
+        // Semicolons inside strings don't split declarations
+        assert!(formatted.contains("  background: url(\"a;b.svg\");\n"), "{}", formatted);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_single_rule_declarations() {
+        assert_eq!(
+            single_rule_declarations(".p-4 { padding: 1rem; margin: 0 }"),
+            Some(vec![
+                ("padding".to_string(), "1rem".to_string()),
+                ("margin".to_string(), "0".to_string()),
+            ])
+        );
+        assert_eq!(single_rule_declarations(".hover\\:p-4:hover { padding: 1rem }"), None);
+        assert_eq!(single_rule_declarations("@media (min-width: 768px) { .md\\:p-4 { padding: 1rem } }"), None);
+    }
+
+    #[test]
+    fn test_selector_class_names_decode_escapes() {
+        assert_eq!(
+            selector_class_names(".w-1\\/2 > .md\\:flex:hover, .\\32 xl\\:p-4"),
+            vec!["w-1/2", "md:flex", "2xl:p-4"]
+        );
+    }
+
+    #[test]
+    fn test_escape_class_round_trips() {
+        let cases = [
+            ("w-1/2", "w-1\\/2"),
+            ("bg-[#fff]", "bg-\\[\\#fff\\]"),
+            ("content-['x']", "content-\\[\\'x\\'\\]"),
+            ("md:hover:flex", "md\\:hover\\:flex"),
+            ("2xl:p-4", "\\32 xl\\:p-4"),
+            ("p-0.5", "p-0\\.5"),
+            ("-mt-2", "-mt-2"),
+        ];
+
+        for (class, expected) in cases {
+            let escaped = escape_class(class);
+            assert_eq!(escaped, expected);
+            assert_eq!(selector_class_names(&format!(".{}:hover", escaped)), vec![class]);
+        }
+    }
+
+    #[test]
+    fn test_unmatched_class_selectors() {
+        let classes = vec!["w-1/2".to_string(), "p-4".to_string()];
+
+        assert!(unmatched_class_selectors(".w-1\\/2 { width: 50% } .p-4 { padding: 1rem }", &classes).is_empty());
+        assert_eq!(
+            unmatched_class_selectors(".w-1/2 { width: 50% } .p-4 { padding: 1rem }", &classes),
+            vec!["w-1/2"]
+        );
+    }
+
+    #[test]
+    fn test_substitute_tailwind_directives() {
+        let template = "/*! app */\n@tailwind base;\n@tailwind components;\n.card { color: red; }\n@tailwind utilities;\n";
+
+        let css = substitute_tailwind_directives(template, "*, ::before { box-sizing: border-box; }\n", ".p-4 { padding: 1rem; }\n");
+
+        assert_eq!(
+            css,
+            "/*! app */\n*, ::before { box-sizing: border-box; }\n\n.card { color: red; }\n.p-4 { padding: 1rem; }\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_css_rules_follows_property_order() {
+        let css = ".hover\\:flex:hover { display: flex }\n.shadow-red-500 { --tw-shadow-color: #ef4444 }\n.shadow-lg { --tw-shadow: 0 10px 15px }\n.flex { display: flex }\n";
+
+        let sorted = sort_css_rules(css);
+
+        let position = |selector: &str| sorted.find(selector).unwrap_or_else(|| panic!("{} in {}", selector, sorted));
+        assert!(position(".flex {") < position(".shadow-lg {"), "{}", sorted);
+        assert!(position(".shadow-lg {") < position(".shadow-red-500 {"), "{}", sorted);
+        assert!(position(".shadow-red-500 {") < position(".hover\\:flex:hover {"), "{}", sorted);
+    }
+
+    #[test]
+    fn test_sort_class_list() {
+        assert_eq!(sort_class_list("text-white flex p-4"), "flex p-4 text-white");
+        assert_eq!(
+            sort_class_list(" hover:bg-blue-600 md:flex bg-blue-500  shadow-lg block "),
+            " block bg-blue-500 shadow-lg md:flex hover:bg-blue-600 "
+        );
+        assert_eq!(sort_class_list(""), "");
+        assert_eq!(sort_class_list("p-4 card flex js-toggle"), "card js-toggle flex p-4");
+        assert_eq!(
+            sort_class_list("p-4 grid-cols-[repeat(3, minmax(0, 1fr))] grid"),
+            "grid grid-cols-[repeat(3, minmax(0, 1fr))] p-4"
+        );
+    }
+
+    #[test]
+    fn test_purge_removes_unused_rules() {
+        let css = "html { margin: 0 }\n.p-4 { padding: 1rem }\n.p-99 { padding: 99rem }\n.w-1\\/2 { width: 50% }\n@media (min-width: 768px) { .md\\:p-99 { padding: 99rem } }";
+        let used: HashSet<String> = ["p-4", "w-1/2"].iter().map(|c| c.to_string()).collect();
+
+        let purged = purge_css(css, &used);
+
+        assert!(!purged.contains("p-99"), "{}", purged);
+        assert!(!purged.contains("@media"), "{}", purged);
+        assert!(purged.contains(".p-4 {"), "{}", purged);
+        assert!(purged.contains(".w-1\\/2 {"), "{}", purged);
+        assert!(purged.contains("html {"), "{}", purged);
+
+        let keyframes = "@keyframes ping { 0% { opacity: 1 } 12.5% { opacity: 0.5 } to { opacity: 0 } }";
+        let purged = purge_css(keyframes, &used);
+        assert!(purged.contains("12.5% {"), "{}", purged);
+        assert!(purged.contains("to {"), "{}", purged);
+        assert!(selector_class_names("12.5%").is_empty());
+    }
+
+    #[test]
+    fn test_minify_css() {
+        let css = "/*! keep */\n/* drop */\n.a > .b,\n.c {\n  padding: 1rem;\n  content: \"a  b\";\n}\n@media (min-width: 768px) {\n  .md\\:flex { display: flex }\n}\n";
+
+        assert_eq!(
+            minify_css(css),
+            "/*! keep */.a>.b,.c{padding:1rem;content:\"a  b\"}@media (min-width: 768px){.md\\:flex{display:flex}}"
+        );
+        assert_eq!(
+            minify_css("[data-x=\"a > b, c\"] > .d ,\n[title='x ~ y'] { color: red }"),
+            "[data-x=\"a > b, c\"]>.d,[title='x ~ y']{color:red}"
+        );
+    }
+
+    #[test]
+    fn test_compact_puts_each_rule_on_one_line() {
+        let formatted = format_css(CSS, CssStyle::Compact);
+
+        assert!(formatted.contains(".p-4 { padding:1rem; }\n"), "{}", formatted);
+        assert!(
+            formatted.contains("@media (min-width: 768px) {\n  .md\\:flex { display: flex; gap: 0.5rem; }\n}\n"),
+            "{}",
+            formatted
+        );
+    }
+}
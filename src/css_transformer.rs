@@ -0,0 +1,46 @@
+//! Class extraction from source CSS files
+//!
+//! Component stylesheets such as `@layer components { .btn { @apply px-4 py-2; } }`
+//! reference utilities through `@apply`. Those utilities are recorded so the
+//! generated bundle contains them; the stylesheet itself is left untouched.
+
+use anyhow::Result;
+
+use crate::ast_transformer::{TransformConfig, TransformMetadata};
+use crate::collector::ClassCollector;
+use crate::css::apply_directive_classes;
+
+/// Record the utilities referenced by `@apply` directives in a stylesheet
+pub fn transform_css_source(
+    source: &str,
+    config: TransformConfig,
+) -> Result<(String, TransformMetadata)> {
+    let mut collector = ClassCollector::new(config);
+    for class in apply_directive_classes(source) {
+        collector.record(&class);
+    }
+
+    Ok((source.to_string(), collector.into_metadata()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_directives_in_layers() {
+        let source = r#"
+@layer components {
+  .btn {
+    @apply px-4 py-2;
+  }
+  .btn-primary { @apply bg-blue-500 text-white !important }
+}
+"#;
+
+        let (transformed, metadata) = transform_css_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["px-4", "py-2", "bg-blue-500", "text-white"]);
+        assert_eq!(transformed, source);
+    }
+}
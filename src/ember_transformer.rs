@@ -0,0 +1,86 @@
+//! Class extraction from Ember template-tag components
+//!
+//! `.gjs`/`.gts` files are JavaScript/TypeScript with embedded
+//! `<template>...</template>` regions. Each region is swapped for a placeholder
+//! identifier, which is valid both where a template is an expression and where
+//! it is a class member, so the rest of the file goes through the AST transformer.
+//! The template markup is processed like Svelte markup and put back in place of
+//! its placeholder afterwards.
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+use crate::ast_transformer::{transform_source, TransformConfig, TransformMetadata};
+use crate::collector::ClassCollector;
+use crate::svelte_transformer::process_markup;
+
+/// Matches `<template>` regions
+fn template_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?s)<template\b[^>]*>.*?</template\s*>").expect("valid template regex"))
+}
+
+/// Matches the placeholders standing in for template regions
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"__tw_template_(\d+)__").expect("valid placeholder regex"))
+}
+
+/// Transform an Ember template-tag component, processing its script and template class markup
+pub fn transform_ember_source(
+    source: &str,
+    config: TransformConfig,
+) -> Result<(String, TransformMetadata)> {
+    let mut collector = ClassCollector::new(config.clone());
+    let mut templates = Vec::new();
+
+    let script = template_regex().replace_all(source, |caps: &Captures| {
+        let placeholder = format!("__tw_template_{}__", templates.len());
+        templates.push(process_markup(&caps[0], &mut collector));
+        placeholder
+    });
+
+    let (code, metadata) = transform_source(&script, config)?;
+    collector.merge(metadata);
+
+    let output = placeholder_regex().replace_all(&code, |caps: &Captures| {
+        caps[1]
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| templates.get(index))
+            .cloned()
+            .unwrap_or_else(|| caps[0].to_string())
+    });
+
+    Ok((output.into_owned(), collector.into_metadata()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_tag_component() {
+        let source = r#"
+import Component from "@glimmer/component";
+
+const title = "font-bold";
+
+export default class Card extends Component {
+  <template><div class="p-4" title="Card">{{yield}}</div></template>
+}
+
+export const Badge = <template><span class='text-sm'>new</span></template>;
+"#;
+
+        let (transformed, metadata) = transform_ember_source(source, TransformConfig::default()).unwrap();
+
+        for class in ["p-4", "text-sm", "font-bold"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+        assert!(!metadata.classes.contains(&"Card".to_string()), "{:?}", metadata.classes);
+        assert!(transformed.contains(r#"<div class="p-4" title="Card">{{yield}}</div>"#), "{}", transformed);
+        assert!(!transformed.contains("__tw_template_"), "{}", transformed);
+    }
+}
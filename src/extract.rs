@@ -0,0 +1,185 @@
+//! Class extraction from in-memory sources
+//!
+//! The library side of the CLI's class collection: each source is routed to the
+//! JavaScript/TypeScript, JSON, Svelte, Ember template-tag or CSS front end by its
+//! file extension.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use std::path::Path;
+
+use crate::ast_transformer::{transform_source, TransformConfig, TransformMetadata};
+use crate::css_transformer::transform_css_source;
+use crate::ember_transformer::transform_ember_source;
+use crate::json_transformer::transform_json_source;
+use crate::svelte_transformer::transform_svelte_source;
+
+/// Extensions with a dedicated front end or known to hold JavaScript/TypeScript
+pub const KNOWN_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts", "json", "svelte", "css", "gjs", "gts",
+];
+
+/// Whether `path` has one of the [`KNOWN_EXTENSIONS`]; other files would be parsed
+/// as JavaScript by [`transform_by_extension`]
+pub fn has_known_extension(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| KNOWN_EXTENSIONS.contains(&ext))
+}
+
+/// Transform one source, picking the front end from the extension of `path`
+pub fn transform_by_extension(
+    path: &str,
+    source: &str,
+    config: TransformConfig,
+) -> Result<(String, TransformMetadata)> {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => transform_json_source(source, config),
+        Some("svelte") => transform_svelte_source(source, config),
+        Some("css") => transform_css_source(source, config),
+        Some("gjs" | "gts") => transform_ember_source(source, config),
+        _ => transform_source(source, config),
+    }
+}
+
+/// Extract the deduplicated classes used by `(path, source)` pairs, in first-seen order
+pub fn extract_classes_from_sources(sources: &[(String, String)]) -> Result<Vec<String>> {
+    Ok(extract_class_files(sources)?.into_keys().collect())
+}
+
+/// Map each class used by `(path, source)` pairs to the paths it appears in.
+///
+/// Classes are in first-seen order; paths keep the order of `sources`.
+pub fn extract_class_files(sources: &[(String, String)]) -> Result<IndexMap<String, Vec<String>>> {
+    let mut class_files: IndexMap<String, Vec<String>> = IndexMap::new();
+    for (path, source) in sources {
+        for class in extract_metadata(path, source)?.classes {
+            class_files.entry(class).or_default().push(path.clone());
+        }
+    }
+    Ok(class_files)
+}
+
+/// Map each path of `(path, source)` pairs to the unique classes it uses, in
+/// first-seen order; the inverse of [`extract_class_files`].
+pub fn extract_file_classes(sources: &[(String, String)]) -> Result<IndexMap<String, Vec<String>>> {
+    let mut file_classes: IndexMap<String, Vec<String>> = IndexMap::new();
+    for (path, source) in sources {
+        let classes = file_classes.entry(path.clone()).or_default();
+        for class in extract_metadata(path, source)?.classes {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+    }
+    Ok(file_classes)
+}
+
+/// Map each class used by `(path, source)` pairs to a `file:line:col` where it appears.
+///
+/// When a class appears in several places the smallest `(file, line, col)` wins, so
+/// the result doesn't depend on the order of `sources`. Classes found by front ends
+/// that don't record positions (JSON, Svelte, CSS) map to just the file.
+pub fn first_class_locations(sources: &[(String, String)]) -> Result<IndexMap<String, String>> {
+    let mut first: IndexMap<String, (&str, Option<(usize, usize)>)> = IndexMap::new();
+    for (path, source) in sources {
+        let metadata = extract_metadata(path, source)?;
+        for class in metadata.classes {
+            let candidate = (path.as_str(), metadata.locations.get(&class).copied());
+            first
+                .entry(class)
+                .and_modify(|current| *current = (*current).min(candidate))
+                .or_insert(candidate);
+        }
+    }
+
+    Ok(first
+        .into_iter()
+        .map(|(class, location)| {
+            let location = match location {
+                (path, Some((line, col))) => format!("{}:{}:{}", path, line, col),
+                (path, None) => path.to_string(),
+            };
+            (class, location)
+        })
+        .collect())
+}
+
+fn extract_metadata(path: &str, source: &str) -> Result<TransformMetadata> {
+    let (_, metadata) = transform_by_extension(path, source, TransformConfig::default())
+        .with_context(|| format!("Failed to extract classes from {}", path))?;
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_classes_from_sources() {
+        let sources = vec![
+            ("Header.jsx".to_string(), r#"const H = () => <header className="flex p-4" />;"#.to_string()),
+            ("tree.json".to_string(), r#"{ "props": { "className": "p-4 m-2" } }"#.to_string()),
+            ("Card.svelte".to_string(), r#"<div class="rounded-md" class:hidden={x}></div>"#.to_string()),
+        ];
+
+        let classes = extract_classes_from_sources(&sources).unwrap();
+
+        assert_eq!(classes, vec!["flex", "p-4", "m-2", "rounded-md", "hidden"]);
+    }
+
+    #[test]
+    fn test_has_known_extension() {
+        let files = ["README.md", "notes.txt", "src/App.jsx", "lib/util.mts", "Card.svelte", "logo.png", "Makefile"];
+
+        let known: Vec<&str> = files.into_iter().filter(|path| has_known_extension(path)).collect();
+
+        assert_eq!(known, vec!["src/App.jsx", "lib/util.mts", "Card.svelte"]);
+    }
+
+    #[test]
+    fn test_extract_class_files() {
+        let sources = vec![
+            ("Header.jsx".to_string(), r#"const H = () => <header className="flex p-4" />;"#.to_string()),
+            ("Footer.jsx".to_string(), r#"const F = () => <footer className="flex m-2" />;"#.to_string()),
+        ];
+
+        let class_files = extract_class_files(&sources).unwrap();
+
+        assert_eq!(class_files["flex"], vec!["Header.jsx", "Footer.jsx"]);
+        assert_eq!(class_files["m-2"], vec!["Footer.jsx"]);
+    }
+
+    #[test]
+    fn test_extract_file_classes() {
+        let sources = vec![
+            ("Header.jsx".to_string(), r#"const H = () => <header className="flex p-4" />;"#.to_string()),
+            ("Footer.jsx".to_string(), r#"const F = () => <footer className="flex m-2" />;"#.to_string()),
+        ];
+
+        let file_classes = extract_file_classes(&sources).unwrap();
+
+        assert_eq!(file_classes["Header.jsx"], vec!["flex", "p-4"]);
+        assert_eq!(file_classes["Footer.jsx"], vec!["flex", "m-2"]);
+    }
+
+    #[test]
+    fn test_first_class_locations_ignore_source_order() {
+        let header = (
+            "b/Header.jsx".to_string(),
+            "const H = () => <header className=\"flex p-4\" />;".to_string(),
+        );
+        let footer = (
+            "a/Footer.jsx".to_string(),
+            "\n\nconst F = () => <footer className=\"flex\" />;".to_string(),
+        );
+
+        let forward = first_class_locations(&[header.clone(), footer.clone()]).unwrap();
+        let reversed = first_class_locations(&[footer, header]).unwrap();
+
+        assert_eq!(forward["flex"], "a/Footer.jsx:3:35");
+        assert_eq!(reversed["flex"], "a/Footer.jsx:3:35");
+        assert_eq!(forward["p-4"], "b/Header.jsx:1:35");
+    }
+}
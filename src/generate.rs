@@ -0,0 +1,342 @@
+//! CSS generation from a list of classes
+//!
+//! This is the library side of the CLI's `generate` mode, free of any file or
+//! stdin handling so embedders can call it with in-memory data.
+
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashMap;
+use tailwind_rs::TailwindBuilder;
+
+use crate::class_utils::split_variants;
+use crate::css::{minify_css, sort_css_rules, sort_css_rules_by, CssOrder};
+
+/// Tailwind's default keyframes for the `animate-*` utilities
+const DEFAULT_KEYFRAMES: &[(&str, &str)] = &[
+    ("spin", "@keyframes spin {\n  to {\n    transform: rotate(360deg);\n  }\n}\n"),
+    (
+        "ping",
+        "@keyframes ping {\n  75%, 100% {\n    transform: scale(2);\n    opacity: 0;\n  }\n}\n",
+    ),
+    ("pulse", "@keyframes pulse {\n  50% {\n    opacity: .5;\n  }\n}\n"),
+    (
+        "bounce",
+        "@keyframes bounce {\n  0%, 100% {\n    transform: translateY(-25%);\n    animation-timing-function: cubic-bezier(0.8, 0, 1, 1);\n  }\n  50% {\n    transform: none;\n    animation-timing-function: cubic-bezier(0, 0, 0.2, 1);\n  }\n}\n",
+    ),
+];
+
+/// Names of the default keyframes used by `animate-*` classes, e.g. `spin` for
+/// `animate-spin` or `hover:animate-spin`
+pub fn animation_keyframes<S: AsRef<str>>(classes: &[S]) -> Vec<String> {
+    let mut names = Vec::new();
+    for class in classes {
+        let (_, base) = split_variants(class.as_ref());
+        let Some(name) = base.trim_start_matches('!').strip_prefix("animate-") else {
+            continue;
+        };
+        if DEFAULT_KEYFRAMES.iter().any(|(known, _)| *known == name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Append the keyframes of `names` that `css` doesn't define yet
+fn ensure_keyframes(mut css: String, names: &[String]) -> String {
+    for (name, block) in DEFAULT_KEYFRAMES {
+        if names.iter().any(|n| n == name) && !css.contains(&format!("@keyframes {}", name)) {
+            if !css.is_empty() && !css.ends_with('\n') {
+                css.push('\n');
+            }
+            css.push_str(block);
+        }
+    }
+    css
+}
+
+/// Options for [`generate_css_from_classes`]
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+    /// Leave out the preflight (reset) styles
+    pub no_preflight: bool,
+    /// Minify the output
+    pub minify: bool,
+    /// Emit obfuscated selectors, matching obfuscated transform output
+    pub obfuscate: bool,
+    /// Classes that keep their readable selector even when obfuscating
+    pub preserve_classes: Vec<String>,
+    /// Order of the rules in the output
+    pub order: CssOrder,
+}
+
+/// Generate the CSS for the given classes.
+///
+/// Classes are traced in the given order, which decides the order of the rules
+/// with [`CssOrder::Bundle`]. Unknown classes are skipped; if bundling fails a warning is printed and the
+/// result is empty. Keyframes needed by `animate-*` utilities are always included.
+pub fn generate_css_from_classes(classes: &[String], options: &GenerateOptions) -> Result<String> {
+    let mut builder = TailwindBuilder::default();
+    builder.preflight.disable = options.no_preflight;
+
+    let classes: Vec<String> = classes.iter().cloned().collect::<IndexSet<_>>().into_iter().collect();
+
+    for class in &classes {
+        // Preserved classes keep their original selector even when obfuscating
+        let obfuscate = options.obfuscate && !options.preserve_classes.contains(class);
+        let _ = builder.trace(class, obfuscate);
+    }
+
+    let keyframes = animation_keyframes(&classes);
+    match builder.bundle().map(|css| ensure_keyframes(css, &keyframes)) {
+        Ok(mut css) => {
+            if options.order == CssOrder::Canonical {
+                css = if options.obfuscate {
+                    // Obfuscated selectors say nothing about the utility; rank by the original
+                    let originals: HashMap<String, String> = obfuscation_map(&classes, options)
+                        .into_iter()
+                        .map(|(original, obfuscated)| (obfuscated, original))
+                        .collect();
+                    sort_css_rules_by(&css, &|class| {
+                        originals.get(class).cloned().unwrap_or_else(|| class.to_string())
+                    })
+                } else {
+                    sort_css_rules(&css)
+                };
+            }
+            if options.minify {
+                css = minify_css(&css);
+            }
+            Ok(css)
+        }
+        Err(e) => {
+            eprintln!("Warning: CSS generation failed: {}", e);
+            Ok(String::new())
+        }
+    }
+}
+
+/// Generate the CSS for the classes in `current` that are not in `previous`, for
+/// appending to the stylesheet generated from `previous`.
+///
+/// Preflight is never included, since the earlier stylesheet already has it. Obfuscated
+/// names depend on the whole class set, so deltas are meant for readable builds.
+pub fn generate_css_delta(previous: &[String], current: &[String], options: &GenerateOptions) -> Result<String> {
+    let added: Vec<String> = current
+        .iter()
+        .filter(|class| !previous.contains(class))
+        .cloned()
+        .collect();
+    if added.is_empty() {
+        return Ok(String::new());
+    }
+
+    let options = GenerateOptions {
+        no_preflight: true,
+        ..options.clone()
+    };
+    generate_css_from_classes(&added, &options)
+}
+
+/// Map each class to the selector it gets when obfuscating with `options`.
+///
+/// Names are assigned in sorted class order, so the map does not depend on the
+/// order in which parallel loaders collected the classes. Classes that fail to
+/// trace are left out.
+pub fn obfuscation_map(classes: &[String], options: &GenerateOptions) -> IndexMap<String, String> {
+    let mut builder = TailwindBuilder::default();
+
+    let mut classes = classes.to_vec();
+    classes.sort_unstable();
+    classes.dedup();
+
+    classes
+        .into_iter()
+        .filter_map(|class| {
+            let obfuscate = !options.preserve_classes.contains(&class);
+            let traced = builder.trace(&class, obfuscate).ok()?.into_owned();
+            Some((class, traced))
+        })
+        .collect()
+}
+
+/// Whether `class` can be an `:export` key. CSS-modules loaders read keys as plain
+/// property names without unescaping them, so only letters, digits, `-` and `_`
+/// round-trip; `hover:p-4` or `w-1/2` would reach JavaScript mangled.
+pub fn is_css_export_key(class: &str) -> bool {
+    !class.is_empty() && class.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Render a class mapping as a CSS-modules `:export` block, so a CSS-modules loader
+/// exposes `styles["bg-blue-500"]` as the obfuscated name.
+///
+/// Classes that fail [`is_css_export_key`] are left out.
+pub fn mappings_to_css_export(map: &IndexMap<String, String>) -> String {
+    let mut css = String::from(":export {\n");
+    for (original, obfuscated) in map.iter().filter(|(original, _)| is_css_export_key(original)) {
+        css.push_str(&format!("  {}: {};\n", original, obfuscated));
+    }
+    css.push_str("}\n");
+    css
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::unmatched_class_selectors;
+
+    #[test]
+    fn test_generate_css_from_classes() {
+        let classes = vec!["p-4".to_string(), "bg-blue-500".to_string(), "not-a-class".to_string()];
+        let options = GenerateOptions {
+            no_preflight: true,
+            ..GenerateOptions::default()
+        };
+
+        let css = generate_css_from_classes(&classes, &options).unwrap();
+
+        assert!(css.contains("padding"), "{}", css);
+        assert!(css.contains("background-color"), "{}", css);
+        assert!(!css.contains("html"), "{}", css);
+    }
+
+    #[test]
+    fn test_generated_selectors_are_escaped() {
+        let classes = vec!["w-1/2".to_string(), "bg-[#fff]".to_string(), "md:flex".to_string()];
+        let options = GenerateOptions {
+            no_preflight: true,
+            ..GenerateOptions::default()
+        };
+
+        let css = generate_css_from_classes(&classes, &options).unwrap();
+
+        assert!(unmatched_class_selectors(&css, &classes).is_empty(), "{}", css);
+    }
+
+    #[test]
+    fn test_generate_css_delta() {
+        let previous = vec!["p-4".to_string()];
+        let current = vec!["p-4".to_string(), "m-2".to_string()];
+
+        let delta = generate_css_delta(&previous, &current, &GenerateOptions::default()).unwrap();
+
+        assert!(delta.contains(".m-2"), "{}", delta);
+        assert!(!delta.contains(".p-4"), "{}", delta);
+        assert!(!delta.contains("html"), "{}", delta);
+        assert_eq!(generate_css_delta(&current, &current, &GenerateOptions::default()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_animation_includes_keyframes() {
+        let classes = vec!["animate-spin".to_string(), "hover:animate-spin".to_string()];
+        let options = GenerateOptions {
+            no_preflight: true,
+            ..GenerateOptions::default()
+        };
+
+        let css = generate_css_from_classes(&classes, &options).unwrap();
+
+        assert!(css.contains(".animate-spin"), "{}", css);
+        assert_eq!(css.matches("@keyframes spin").count(), 1, "{}", css);
+        assert_eq!(animation_keyframes(&classes), vec!["spin"]);
+    }
+
+    #[test]
+    fn test_canonical_order_keeps_shadow_color_after_shadow() {
+        let classes = vec!["shadow-red-500".to_string(), "shadow-lg".to_string()];
+        let readable = GenerateOptions {
+            no_preflight: true,
+            order: CssOrder::Canonical,
+            ..GenerateOptions::default()
+        };
+        let obfuscated = GenerateOptions {
+            obfuscate: true,
+            ..readable.clone()
+        };
+
+        let css = generate_css_from_classes(&classes, &readable).unwrap();
+        let shadow = css.find(".shadow-lg {").expect("shadow rule");
+        let color = css.find(".shadow-red-500 {").expect("shadow color rule");
+        assert!(shadow < color, "{}", css);
+
+        let map = obfuscation_map(&classes, &obfuscated);
+        let css = generate_css_from_classes(&classes, &obfuscated).unwrap();
+        let shadow = css.find(&format!(".{} {{", map["shadow-lg"])).expect("obfuscated shadow rule");
+        let color = css.find(&format!(".{} {{", map["shadow-red-500"])).expect("obfuscated color rule");
+        assert!(shadow < color, "{}", css);
+    }
+
+    #[test]
+    fn test_obfuscation_map_is_independent_of_class_order() {
+        let classes: Vec<String> = ["p-4", "bg-blue-500", "text-white", "hover:bg-red-500"]
+            .iter()
+            .map(|class| class.to_string())
+            .collect();
+        let mut reversed = classes.clone();
+        reversed.reverse();
+
+        let first = obfuscation_map(&classes, &GenerateOptions::default());
+        let second = obfuscation_map(&reversed, &GenerateOptions::default());
+
+        assert_eq!(first.len(), classes.len());
+        assert_eq!(first, second);
+        assert!(first.iter().eq(second.iter()));
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_obfuscation_map_matches_transform() {
+        let source = r#"const El = () => <div className="text-white p-4 hover:bg-red-500 bg-blue-500">Hi</div>;"#;
+        let config = crate::TransformConfig {
+            obfuscate: true,
+            ..crate::TransformConfig::default()
+        };
+        let (transformed, metadata) = crate::transform_source(source, config).unwrap();
+
+        let map = obfuscation_map(&metadata.classes, &GenerateOptions::default());
+
+        assert_eq!(map.len(), 4);
+        let expected: Vec<&str> = ["text-white", "p-4", "hover:bg-red-500", "bg-blue-500"]
+            .iter()
+            .map(|class| map[*class].as_str())
+            .collect();
+        assert!(
+            transformed.contains(&format!("className=\"{}\"", expected.join(" "))),
+            "{} vs {:?}",
+            transformed,
+            map
+        );
+    }
+
+    #[test]
+    fn test_mappings_to_css_export() {
+        let classes = vec!["bg-blue-500".to_string(), "hover:p-4".to_string()];
+        let map = obfuscation_map(&classes, &GenerateOptions::default());
+
+        let css = mappings_to_css_export(&map);
+
+        assert!(css.starts_with(":export {\n"), "{}", css);
+        assert!(css.ends_with("}\n"), "{}", css);
+        assert!(css.contains(&format!("  bg-blue-500: {};\n", map["bg-blue-500"])), "{}", css);
+        assert!(!css.contains("hover"), "{}", css);
+        assert_eq!(css.lines().count(), 3);
+        assert!(is_css_export_key("bg-blue-500"));
+        assert!(!is_css_export_key("hover:p-4"));
+        assert!(!is_css_export_key("w-1/2"));
+    }
+
+    #[test]
+    fn test_canonical_order_puts_layout_before_color() {
+        let classes = vec!["bg-blue-500".to_string(), "flex".to_string()];
+        let options = GenerateOptions {
+            no_preflight: true,
+            order: CssOrder::Canonical,
+            ..GenerateOptions::default()
+        };
+
+        let css = generate_css_from_classes(&classes, &options).unwrap();
+
+        let flex = css.find(".flex").expect("flex rule");
+        let bg = css.find(".bg-blue-500").expect("bg rule");
+        assert!(flex < bg, "{}", css);
+    }
+}
@@ -0,0 +1,187 @@
+//! Class extraction from JSON/JSONC component trees
+//!
+//! Low-code tools store component trees as JSON documents where class strings live
+//! under `className`/`class` (or configured class attribute) keys. This module walks such documents directly,
+//! bypassing the JavaScript parser, and processes those values the same way the
+//! AST transformer processes className string literals.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::ast_transformer::{TransformConfig, TransformMetadata};
+use crate::collector::ClassCollector;
+
+/// Walks a JSON document transforming class strings found under class keys
+struct JsonClassScanner {
+    /// Traces and records class strings
+    collector: ClassCollector,
+}
+
+impl JsonClassScanner {
+    /// Recursively visit a JSON value, processing class strings in place
+    fn visit(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    if self.collector.config().is_class_attribute(key) {
+                        if let Value::String(class_string) = child {
+                            *class_string = self.collector.process_string(class_string);
+                            continue;
+                        }
+                    }
+                    self.visit(child);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.visit(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Remove `//` and `/* */` comments from JSONC input, leaving string contents intact
+fn strip_json_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(ch) = chars.next() {
+        if in_string {
+            output.push(ch);
+            match ch {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        output.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (ch, chars.peek().copied()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(ch);
+            }
+            ('/', Some('/')) => {
+                // Line comment: skip to end of line but keep the newline
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => output.push(ch),
+        }
+    }
+
+    output
+}
+
+/// Transform a JSON/JSONC document, processing class strings under `className`/`class` keys
+pub fn transform_json_source(
+    source: &str,
+    config: TransformConfig,
+) -> Result<(String, TransformMetadata)> {
+    let mut document: Value = match serde_json::from_str(&strip_json_comments(source)) {
+        Ok(document) => document,
+        Err(err) => {
+            // Log error to stderr and return original source
+            eprintln!("JSON parsing error: {}", err);
+            return Ok((
+                source.to_string(),
+                TransformMetadata {
+                    classes: vec![],
+                    original_count: 0,
+                    warnings: vec![],
+                    locations: IndexMap::new(),
+                },
+            ));
+        }
+    };
+
+    let mut scanner = JsonClassScanner {
+        collector: ClassCollector::new(config),
+    };
+    scanner.visit(&mut document);
+
+    let code = serde_json::to_string_pretty(&document).context("Failed to serialize JSON")?;
+
+    Ok((code, scanner.collector.into_metadata()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_json_class_names() {
+        let source = r#"{
+            "type": "Page",
+            "children": [
+                {
+                    "type": "Card",
+                    "props": { "className": "flex p-4", "title": "shadow-lg" }
+                }
+            ]
+        }"#;
+
+        let (_, metadata) = transform_json_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["flex".to_string(), "p-4".to_string()]);
+        assert_eq!(metadata.original_count, 2);
+    }
+
+    #[test]
+    fn test_jsonc_comments_are_ignored() {
+        let source = r#"{
+            // component tree exported by the editor
+            "root": { /* inline */ "class": "text-center", "href": "https://example.com" }
+        }"#;
+
+        let (transformed, metadata) = transform_json_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(metadata.classes, vec!["text-center".to_string()]);
+        assert!(transformed.contains("https://example.com"));
+    }
+
+    #[test]
+    fn test_configured_class_attribute_keys() {
+        let source = r#"{ "props": { "wrapperClass": "grid gap-2", "label": "shadow-lg" } }"#;
+        let config = TransformConfig {
+            class_attributes: vec!["wrapperClass".to_string()],
+            ..TransformConfig::default()
+        };
+
+        let (_, metadata) = transform_json_source(source, config).unwrap();
+
+        assert_eq!(metadata.classes, vec!["grid".to_string(), "gap-2".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_json_returns_original() {
+        let source = r#"{ "className": "flex" "#;
+
+        let (transformed, metadata) = transform_json_source(source, TransformConfig::default()).unwrap();
+
+        assert_eq!(transformed, source);
+        assert!(metadata.classes.is_empty());
+    }
+}
@@ -5,17 +5,84 @@
 //! and other systems that need to extract and process Tailwind classes from JavaScript/TypeScript.
 
 pub mod processor;
+pub mod class_utils;
+pub mod css;
+pub mod output;
+pub mod generate;
+
+// Peak heap tracking for `--report-memory` (no allocator is installed by default)
+#[cfg(feature = "memory-stats")]
+pub mod memory;
 
 // AST transformation module (only available with swc_core feature)
 #[cfg(feature = "cli")]
 pub mod ast_transformer;
 
+// JSON component tree scanning (shares the transformer's class parsing)
+#[cfg(feature = "cli")]
+pub mod json_transformer;
+
+// Svelte component scanning
+#[cfg(feature = "cli")]
+pub mod svelte_transformer;
+
+// Ember template-tag components (.gjs/.gts)
+#[cfg(feature = "cli")]
+pub mod ember_transformer;
+
+// Source stylesheet scanning for `@apply` directives
+#[cfg(feature = "cli")]
+pub mod css_transformer;
+
+// Extension-based dispatch over the transformers for in-memory sources
+#[cfg(feature = "cli")]
+pub mod extract;
+
+// Trace-and-record bookkeeping shared by the non-JavaScript front ends
+#[cfg(feature = "cli")]
+mod collector;
+
 // Re-export the main trait at the crate root for convenience
-pub use processor::TailwindClassProcessor;
+pub use processor::{TailwindClassProcessor, WhitespaceContext, UNTRACED_MARKER};
+
+// Re-export class inspection helpers
+pub use class_utils::{
+    classify_class, dedup_case_insensitive, is_class_like, redundant_variants, requires_css_escape,
+    uses_arbitrary_value, validate_class, variant_breakdown, ClassCategory, ClassClassification, ClassRejection, VariantKind,
+    MAX_CLASS_LENGTH,
+};
+
+// Re-export CSS post-processing helpers
+pub use css::{
+    apply_directive_classes, escape_class, format_css, minify_css, purge_css, sort_css_rules,
+    sort_class_list, substitute_tailwind_directives, unmatched_class_selectors, CssOrder, CssStyle,
+};
+
+// Re-export output sinks
+pub use output::{FileSink, MemorySink, OutputSink};
+
+// Re-export CSS generation
+pub use generate::{
+    animation_keyframes, generate_css_delta, generate_css_from_classes, is_css_export_key, mappings_to_css_export,
+    obfuscation_map, GenerateOptions,
+};
 
 // Re-export TailwindBuilder for consumers who need it
 pub use tailwind_rs::TailwindBuilder;
 
 // Re-export AST transformation functionality when available
 #[cfg(feature = "cli")]
-pub use ast_transformer::{transform_source, TransformConfig, TransformMetadata};
\ No newline at end of file
+pub use ast_transformer::{transform_source, TransformConfig, TransformMetadata, DEFAULT_MAX_NESTING_DEPTH};
+#[cfg(feature = "cli")]
+pub use json_transformer::transform_json_source;
+#[cfg(feature = "cli")]
+pub use svelte_transformer::transform_svelte_source;
+#[cfg(feature = "cli")]
+pub use css_transformer::transform_css_source;
+#[cfg(feature = "cli")]
+pub use ember_transformer::transform_ember_source;
+#[cfg(feature = "cli")]
+pub use extract::{
+    extract_class_files, extract_classes_from_sources, extract_file_classes, first_class_locations, has_known_extension,
+    transform_by_extension, KNOWN_EXTENSIONS,
+};
\ No newline at end of file
@@ -0,0 +1,106 @@
+//! Peak heap usage tracking
+//!
+//! [`PeakAlloc`] wraps another allocator and records the high-water mark of live
+//! heap bytes. It only measures anything when a binary installs it as the
+//! `#[global_allocator]`, which the CLI does under the `memory-stats` feature.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator wrapper that tracks current and peak live heap bytes
+pub struct PeakAlloc<A> {
+    inner: A,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl<A> PeakAlloc<A> {
+    /// Wrap an allocator
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Live heap bytes right now
+    pub fn current_bytes(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Highest live heap bytes since the last reset
+    pub fn peak_bytes(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Start a new measurement window; returns the current live bytes as its baseline
+    pub fn reset_peak(&self) -> usize {
+        let current = self.current_bytes();
+        self.peak.store(current, Ordering::Relaxed);
+        current
+    }
+
+    fn record_alloc(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for PeakAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.record_dealloc(layout.size());
+            self.record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::System;
+
+    #[test]
+    fn test_peak_survives_deallocation() {
+        let alloc = PeakAlloc::new(System);
+        let layout = Layout::from_size_align(4096, 8).unwrap();
+
+        let baseline = alloc.reset_peak();
+        unsafe {
+            let ptr = alloc.alloc(layout);
+            assert!(!ptr.is_null());
+            alloc.dealloc(ptr, layout);
+        }
+
+        assert_eq!(alloc.current_bytes(), baseline);
+        assert_eq!(alloc.peak_bytes() - baseline, 4096);
+    }
+}
@@ -0,0 +1,117 @@
+//! Destinations for generated artifacts
+//!
+//! The CLI writes CSS to stdout and metadata to local files, but embedders (for
+//! example serverless builds uploading to object storage) can route the same
+//! bytes anywhere by implementing [`OutputSink`].
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Receives the generated CSS and metadata manifest
+pub trait OutputSink {
+    /// Write the generated stylesheet
+    fn write_css(&self, css: &[u8]) -> Result<()>;
+
+    /// Write the metadata manifest JSON
+    fn write_manifest(&self, manifest: &[u8]) -> Result<()>;
+}
+
+/// Filesystem sink; artifacts without a configured path go to stdout
+#[derive(Debug, Clone, Default)]
+pub struct FileSink {
+    /// Path for the CSS output
+    pub css_path: Option<PathBuf>,
+    /// Path for the metadata manifest
+    pub manifest_path: Option<PathBuf>,
+}
+
+impl FileSink {
+    fn write(path: &Option<PathBuf>, bytes: &[u8], what: &str) -> Result<()> {
+        match path {
+            Some(path) => fs::write(path, bytes)
+                .with_context(|| format!("Failed to write {} to {:?}", what, path)),
+            None => io::stdout()
+                .write_all(bytes)
+                .with_context(|| format!("Failed to write {} to stdout", what)),
+        }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn write_css(&self, css: &[u8]) -> Result<()> {
+        Self::write(&self.css_path, css, "CSS")
+    }
+
+    fn write_manifest(&self, manifest: &[u8]) -> Result<()> {
+        Self::write(&self.manifest_path, manifest, "metadata")
+    }
+}
+
+/// In-memory sink, useful for embedding and tests
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    css: Mutex<Vec<u8>>,
+    manifest: Mutex<Vec<u8>>,
+}
+
+impl MemorySink {
+    /// Bytes written as CSS so far
+    pub fn css(&self) -> Vec<u8> {
+        self.css.lock().expect("css buffer poisoned").clone()
+    }
+
+    /// Bytes written as the manifest so far
+    pub fn manifest(&self) -> Vec<u8> {
+        self.manifest.lock().expect("manifest buffer poisoned").clone()
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write_css(&self, css: &[u8]) -> Result<()> {
+        self.css.lock().expect("css buffer poisoned").extend_from_slice(css);
+        Ok(())
+    }
+
+    fn write_manifest(&self, manifest: &[u8]) -> Result<()> {
+        self.manifest
+            .lock()
+            .expect("manifest buffer poisoned")
+            .extend_from_slice(manifest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_sink_receives_bytes() {
+        let sink = MemorySink::default();
+        let dyn_sink: &dyn OutputSink = &sink;
+
+        dyn_sink.write_css(b".p-4{padding:1rem}").unwrap();
+        dyn_sink.write_manifest(br#"{"classes":["p-4"]}"#).unwrap();
+
+        assert_eq!(sink.css(), b".p-4{padding:1rem}");
+        assert_eq!(sink.manifest(), br#"{"classes":["p-4"]}"#);
+    }
+
+    #[test]
+    fn test_file_sink_writes_configured_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FileSink {
+            css_path: Some(dir.path().join("out.css")),
+            manifest_path: Some(dir.path().join("manifest.json")),
+        };
+
+        sink.write_css(b".flex{display:flex}").unwrap();
+        sink.write_manifest(b"{}").unwrap();
+
+        assert_eq!(fs::read(dir.path().join("out.css")).unwrap(), b".flex{display:flex}");
+        assert_eq!(fs::read(dir.path().join("manifest.json")).unwrap(), b"{}");
+    }
+}
@@ -1,5 +1,37 @@
 use tailwind_rs::TailwindBuilder;
 
+use crate::class_utils::is_obviously_custom;
+
+/// Prefix given to classes that fail to trace when marking is enabled
+pub const UNTRACED_MARKER: &str = "UNTRACED-";
+
+/// Whether trace() would pass every class of the string through unchanged
+fn only_custom_classes(class_string: &str) -> bool {
+    class_string.split_whitespace().all(is_obviously_custom)
+}
+
+/// Where a class string sits in the source, which decides whether the whitespace
+/// at its edges is meaningful
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhitespaceContext {
+    /// A fragment joined with other strings (`" flex " + x`, template quasis);
+    /// edge whitespace separates it from its neighbours and is kept
+    Concatenation,
+    /// A complete class list such as a JSX `className="..."` literal; edge
+    /// whitespace is trimmed
+    Standalone,
+}
+
+impl WhitespaceContext {
+    /// Apply the context to a processed class string
+    pub fn normalize(self, class_string: &str) -> String {
+        match self {
+            WhitespaceContext::Concatenation => class_string.to_string(),
+            WhitespaceContext::Standalone => class_string.trim().to_string(),
+        }
+    }
+}
+
 /// Trait for processing Tailwind and custom classes.
 /// 
 /// This trait provides the shared logic for transforming class strings that may contain
@@ -26,12 +58,55 @@ pub trait TailwindClassProcessor {
     /// # Returns
     /// The processed class string with Tailwind transformations applied
     fn process_with_fallback(&mut self, class_string: &str, obfuscate: bool) -> String {
+        if only_custom_classes(class_string) {
+            return class_string.to_string();
+        }
+
         // trace() will process Tailwind classes and pass through custom classes unchanged
         match self.tailwind_builder().trace(class_string, obfuscate) {
             Ok(result) =>  result.into_owned(),
             Err(_) => class_string.to_string(), // Fallback to original on error
         }
     }
+
+    /// Process a class string, prefixing classes that fail to trace with
+    /// [`UNTRACED_MARKER`] instead of silently passing them through.
+    ///
+    /// When the whole string fails, each class is retraced on its own to find the
+    /// offenders; whitespace is kept as is.
+    fn process_marking_untraced(&mut self, class_string: &str, obfuscate: bool) -> String {
+        if only_custom_classes(class_string) {
+            return class_string.to_string();
+        }
+        if let Ok(result) = self.tailwind_builder().trace(class_string, obfuscate) {
+            return result.into_owned();
+        }
+
+        let mut output = String::with_capacity(class_string.len());
+        let mut rest = class_string;
+        while !rest.is_empty() {
+            let in_whitespace = rest.starts_with(char::is_whitespace);
+            let split = rest
+                .find(|c: char| c.is_whitespace() != in_whitespace)
+                .unwrap_or(rest.len());
+            let (segment, tail) = rest.split_at(split);
+
+            if in_whitespace {
+                output.push_str(segment);
+            } else {
+                match self.tailwind_builder().trace(segment, obfuscate) {
+                    Ok(result) => output.push_str(&result),
+                    Err(_) => {
+                        output.push_str(UNTRACED_MARKER);
+                        output.push_str(segment);
+                    }
+                }
+            }
+            rest = tail;
+        }
+
+        output
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +177,49 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_custom_class_fast_path_matches_trace() {
+        let test_cases = vec![
+            "btn btn-primary",
+            " card card-header is-open ",
+            "nav-link active",
+        ];
+
+        for input in test_cases {
+            let mut processor = TestProcessor::new();
+            let fast = processor.process_with_fallback(input, true);
+
+            let mut builder = TailwindBuilder::default();
+            let traced = builder.trace(input, true).unwrap().into_owned();
+
+            assert_eq!(fast, traced, "Fast path diverged from trace() for '{}'", input);
+        }
+    }
+
+    #[test]
+    fn test_whitespace_context() {
+        let mut processor = TestProcessor::new();
+        let processed = processor.process_with_fallback(" flex ", false);
+
+        assert_eq!(WhitespaceContext::Concatenation.normalize(&processed), " flex ");
+        assert_eq!(WhitespaceContext::Standalone.normalize(&processed), "flex");
+    }
+
+    #[test]
+    fn test_mark_untraced_classes() {
+        // A dangling arbitrary value can't be parsed by tailwind-rs
+        let broken = "w-[";
+        assert!(TailwindBuilder::default().trace(broken, false).is_err());
+
+        let input = "flex w-[  p-4";
+
+        let mut processor = TestProcessor::new();
+        assert_eq!(processor.process_with_fallback(input, false), input);
+
+        let mut processor = TestProcessor::new();
+        let marked = processor.process_marking_untraced(input, false);
+        assert!(marked.contains("UNTRACED-w-[  "), "{}", marked);
+        assert!(!marked.contains("UNTRACED-flex"), "{}", marked);
+    }
 }
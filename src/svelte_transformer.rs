@@ -0,0 +1,130 @@
+//! Class extraction from Svelte components
+//!
+//! A `.svelte` file is markup with embedded `<script>` and `<style>` blocks, which
+//! the JavaScript parser rejects as a whole. Script blocks are run through the AST
+//! transformer as TypeScript; in the markup, static `class="..."` (and configured
+//! class attribute) values and the class name of `class:<name>` directives are
+//! processed. Style blocks are
+//! left untouched.
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+use crate::ast_transformer::{transform_source, TransformConfig, TransformMetadata};
+use crate::collector::ClassCollector;
+
+/// Matches `<script>` blocks (capturing tag and body) and `<style>` blocks
+fn block_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?s)(?P<open><script\b[^>]*>)(?P<body>.*?)(?P<close></script\s*>)|<style\b[^>]*>.*?</style\s*>")
+            .expect("valid block regex")
+    })
+}
+
+/// Matches static attributes; values containing `{` are dynamic and skipped
+fn attr_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?P<prefix>\s(?P<name>[A-Za-z][\w-]*)\s*=\s*)(?:"(?P<dq>[^"{]*)"|'(?P<sq>[^'{]*)')"#)
+            .expect("valid class attribute regex")
+    })
+}
+
+/// Matches `class:<name>` directives
+fn class_directive_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?P<prefix>\sclass:)(?P<name>[^\s=>/]+)").expect("valid class directive regex")
+    })
+}
+
+/// Process class attributes and directives in a markup fragment
+pub(crate) fn process_markup(markup: &str, collector: &mut ClassCollector) -> String {
+    let with_attrs = attr_regex().replace_all(markup, |caps: &Captures| {
+        if !collector.config().is_class_attribute(&caps["name"]) {
+            return caps[0].to_string();
+        }
+        let (value, quote) = match caps.name("dq") {
+            Some(value) => (value.as_str(), '"'),
+            None => (caps.name("sq").map_or("", |m| m.as_str()), '\''),
+        };
+        let processed = collector.process_string(value);
+        format!("{}{}{}{}", &caps["prefix"], quote, processed, quote)
+    });
+
+    class_directive_regex()
+        .replace_all(&with_attrs, |caps: &Captures| {
+            let processed = collector.process_string(&caps["name"]);
+            format!("{}{}", &caps["prefix"], processed)
+        })
+        .into_owned()
+}
+
+/// Transform a Svelte component, processing its script blocks and class markup
+pub fn transform_svelte_source(
+    source: &str,
+    config: TransformConfig,
+) -> Result<(String, TransformMetadata)> {
+    let mut collector = ClassCollector::new(config.clone());
+    let mut output = String::with_capacity(source.len());
+    let mut last_end = 0;
+
+    for block in block_regex().captures_iter(source) {
+        let whole = block.get(0).expect("match has a whole group");
+        output.push_str(&process_markup(&source[last_end..whole.start()], &mut collector));
+
+        match block.name("body") {
+            Some(body) => {
+                let (code, metadata) = transform_source(body.as_str(), config.clone())?;
+                collector.merge(metadata);
+                output.push_str(&block["open"]);
+                output.push_str(&code);
+                output.push_str(&block["close"]);
+            }
+            None => output.push_str(whole.as_str()),
+        }
+
+        last_end = whole.end();
+    }
+
+    output.push_str(&process_markup(&source[last_end..], &mut collector));
+
+    Ok((output, collector.into_metadata()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svelte_class_attribute_and_directive() {
+        let source = r#"
+<script lang="ts">
+  export let open: boolean = false;
+  const title: string = "font-bold";
+</script>
+
+<div class="p-4" class:hidden={!open}>
+  <span class='text-sm' class={dynamic}>Hello</span>
+</div>
+
+<style>
+  .local { color: red; }
+</style>
+"#;
+
+        let (transformed, metadata) = transform_svelte_source(source, TransformConfig::default()).unwrap();
+
+        for class in ["font-bold", "p-4", "hidden", "text-sm"] {
+            assert!(metadata.classes.contains(&class.to_string()), "Missing class: {}", class);
+        }
+        assert!(!metadata.classes.contains(&"local".to_string()));
+
+        // Markup structure and style blocks survive the transform
+        assert!(transformed.contains("class:hidden={!open}"), "{}", transformed);
+        assert!(transformed.contains("class={dynamic}"), "{}", transformed);
+        assert!(transformed.contains(".local { color: red; }"), "{}", transformed);
+    }
+}